@@ -9,11 +9,17 @@
 // except according to those terms.
 
 use remove_dir_all::remove_dir_all;
+use std::collections::VecDeque;
+use std::ffi::OsStr;
 use std::mem;
 use std::path::{self, Path, PathBuf};
-use std::{fmt, fs, io};
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::{Condvar, Mutex, Once};
+use std::{fmt, fs, io, thread};
 
-use crate::error::IoResultExt;
+use crate::error::{IoResultExt, Operation};
+use crate::util;
 use crate::Builder;
 
 /// Create a new temporary directory.
@@ -194,6 +200,7 @@ pub fn tempdir_in<P: AsRef<Path>>(dir: P) -> io::Result<TempDir> {
 /// [`std::process::exit()`]: http://doc.rust-lang.org/std/process/fn.exit.html
 pub struct TempDir {
     path: Box<Path>,
+    background_drop: bool,
 }
 
 impl TempDir {
@@ -264,6 +271,28 @@ impl TempDir {
         Builder::new().tempdir_in(dir)
     }
 
+    /// Create a new temporary directory holding a copy of the directory
+    /// `src`.
+    ///
+    /// See [`Builder::tempdir_from`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tempfile::TempDir;
+    ///
+    /// # use std::io;
+    /// # fn run() -> Result<(), io::Error> {
+    /// let fixtures = TempDir::copy_from("tests/fixtures")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Builder::tempdir_from`]: struct.Builder.html#method.tempdir_from
+    pub fn copy_from<P: AsRef<Path>>(src: P) -> io::Result<TempDir> {
+        Builder::new().tempdir_from(src)
+    }
+
     /// Accesses the [`Path`] to the temporary directory.
     ///
     /// [`Path`]: http://doc.rust-lang.org/std/path/struct.Path.html
@@ -375,7 +404,7 @@ impl TempDir {
     /// # }
     /// ```
     pub fn close(mut self) -> io::Result<()> {
-        let result = remove_dir_all(self.path()).with_err_path(|| self.path());
+        let result = remove_dir_all(self.path()).with_err_path(Operation::Remove, || self.path());
 
         // Set self.path to empty Box to release the memory, since an empty
         // Box does not allocate any heap memory.
@@ -386,6 +415,41 @@ impl TempDir {
 
         result
     }
+
+    /// Closes the temporary directory, removing it on a background thread.
+    ///
+    /// The directory is first renamed to a hidden name in the same parent
+    /// directory, so its path is freed immediately, and then removed by a
+    /// dedicated cleanup thread. Any errors are ignored. Use
+    /// [`flush_background_cleanup()`] to wait for pending removals to finish,
+    /// e.g. before the program exits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tempfile::TempDir;
+    ///
+    /// # use std::io;
+    /// # fn run() -> Result<(), io::Error> {
+    /// let tmp_dir = TempDir::new()?;
+    /// let path = tmp_dir.path().to_owned();
+    ///
+    /// tmp_dir.close_in_background();
+    /// assert!(!path.exists());
+    ///
+    /// tempfile::flush_background_cleanup();
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`flush_background_cleanup()`]: fn.flush_background_cleanup.html
+    pub fn close_in_background(mut self) {
+        let path = mem::replace(&mut self.path, PathBuf::new().into_boxed_path());
+        remove_in_background(path.into());
+
+        // Prevent the Drop impl from being called.
+        mem::forget(self);
+    }
 }
 
 impl AsRef<Path> for TempDir {
@@ -404,14 +468,246 @@ impl fmt::Debug for TempDir {
 
 impl Drop for TempDir {
     fn drop(&mut self) {
-        let _ = remove_dir_all(self.path());
+        if self.background_drop {
+            remove_in_background(self.path().to_owned());
+        } else {
+            let _ = remove_dir_all(self.path());
+        }
     }
 }
 
-pub(crate) fn create(path: PathBuf) -> io::Result<TempDir> {
+/// How [`Builder::tempdir_from`] populates a temporary directory from a
+/// template.
+///
+/// [`Builder::tempdir_from`]: struct.Builder.html#method.tempdir_from
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CopyMode {
+    /// Copy regular files.
+    ///
+    /// On Linux, files are reflinked where the filesystem supports it (as
+    /// with [`CopyMode::Reflink`]), and copied in the kernel with
+    /// `copy_file_range` otherwise. Either way, modifying the copy never
+    /// affects the template.
+    ///
+    /// [`CopyMode::Reflink`]: #variant.Reflink
+    Copy,
+    /// Hard link regular files to the template.
+    ///
+    /// This is the fastest option, but the linked files must not be modified
+    /// in place, as the changes would be visible in the template too. Files
+    /// can only be linked within a filesystem, so if the template is on a
+    /// different filesystem than the temporary directory (for example, a
+    /// fixture in the source tree and a temporary directory on a `tmpfs`),
+    /// they are copied instead.
+    Hardlink,
+    /// Reflink regular files to the template, sharing their underlying
+    /// extents until either side is modified.
+    ///
+    /// This is as cheap as hard linking, but modifying the copy never affects
+    /// the template. It is only supported on Linux, on filesystems that
+    /// support reflinks (such as Btrfs and XFS), and only within a
+    /// filesystem; otherwise, populating the directory fails.
+    Reflink,
+}
+
+pub(crate) fn create(path: PathBuf, background_drop: bool) -> io::Result<TempDir> {
     fs::create_dir(&path)
-        .with_err_path(|| &path)
+        .with_err_path(Operation::Create, || &path)
         .map(|_| TempDir {
             path: path.into_boxed_path(),
+            background_drop,
         })
 }
+
+/// Wait for all directories scheduled for background removal to be removed.
+///
+/// See [`TempDir::close_in_background()`] and [`Builder::background_drop()`].
+///
+/// [`TempDir::close_in_background()`]: struct.TempDir.html#method.close_in_background
+/// [`Builder::background_drop()`]: struct.Builder.html#method.background_drop
+pub fn flush_background_cleanup() {
+    // Nothing was ever scheduled, don't start the cleanup thread just to wait
+    // for it.
+    let cleanup = match Cleanup::get_if_started() {
+        Some(cleanup) => cleanup,
+        None => return,
+    };
+    let mut state = cleanup.state.lock().unwrap();
+    while state.pending > 0 {
+        state = cleanup.done.wait(state).unwrap();
+    }
+}
+
+/// Renames `path` out of the way and hands it to the cleanup thread.
+fn remove_in_background(path: PathBuf) {
+    // Free the path right away by renaming the directory to a hidden name next
+    // to it. If that fails, remove it under its original name.
+    let path = match path.parent() {
+        Some(parent) => util::create_helper(
+            parent,
+            OsStr::new(".tmp-delete"),
+            OsStr::new(""),
+            crate::NUM_RAND_CHARS,
+            |tombstone| {
+                // Renaming onto an existing empty directory succeeds on UNIX,
+                // which would defeat the retry on name collisions, so create
+                // the hidden directory first and move `path` into it.
+                fs::create_dir(&tombstone)?;
+                let name = path.file_name().unwrap_or_else(|| OsStr::new("dir"));
+                match fs::rename(&path, tombstone.join(name)) {
+                    Ok(()) => Ok(tombstone),
+                    Err(e) => {
+                        let _ = fs::remove_dir(&tombstone);
+                        Err(e)
+                    }
+                }
+            },
+        )
+        .unwrap_or(path),
+        None => path,
+    };
+    Cleanup::get().push(path);
+}
+
+/// The queue of directories waiting to be removed by the cleanup thread.
+struct Cleanup {
+    state: Mutex<CleanupState>,
+    work: Condvar,
+    done: Condvar,
+}
+
+struct CleanupState {
+    queue: VecDeque<PathBuf>,
+    pending: usize,
+    running: bool,
+}
+
+static INIT: Once = Once::new();
+/// Points to the leaked `Cleanup` once `INIT` has run, and is null before.
+static CLEANUP: AtomicPtr<Cleanup> = AtomicPtr::new(ptr::null_mut());
+
+impl Cleanup {
+    /// Returns the cleanup queue, starting the cleanup thread if needed.
+    fn get() -> &'static Cleanup {
+        INIT.call_once(|| {
+            let cleanup: &'static Cleanup = Box::leak(Box::new(Cleanup {
+                state: Mutex::new(CleanupState {
+                    queue: VecDeque::new(),
+                    pending: 0,
+                    running: false,
+                }),
+                work: Condvar::new(),
+                done: Condvar::new(),
+            }));
+            let running = thread::Builder::new()
+                .name("tempfile-cleanup".into())
+                .spawn(move || cleanup.run())
+                .is_ok();
+            cleanup.state.lock().unwrap().running = running;
+            CLEANUP.store(cleanup as *const Cleanup as *mut Cleanup, Ordering::Release);
+        });
+        Cleanup::get_if_started().expect("cleanup queue not initialized")
+    }
+
+    /// Returns the cleanup queue, if anything was ever scheduled for removal.
+    fn get_if_started() -> Option<&'static Cleanup> {
+        let cleanup = CLEANUP.load(Ordering::Acquire);
+        // SAFETY: `CLEANUP` is only ever set, once, at the end of the
+        // `call_once` in `get`, to a `Cleanup` that was fully initialized
+        // before (the `Release` store pairs with this `Acquire` load) and is
+        // leaked, so never freed. It's only ever read as a shared reference.
+        // Before that, it's null and `as_ref` returns `None`.
+        unsafe { cleanup.as_ref() }
+    }
+
+    fn push(&self, path: PathBuf) {
+        let mut state = self.state.lock().unwrap();
+        if !state.running {
+            // There's no cleanup thread, do it ourselves.
+            drop(state);
+            let _ = remove_dir_all(&path);
+            return;
+        }
+        state.queue.push_back(path);
+        state.pending += 1;
+        self.work.notify_one();
+    }
+
+    fn run(&self) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            match state.queue.pop_front() {
+                Some(path) => {
+                    drop(state);
+                    let _ = remove_dir_all(&path);
+                    state = self.state.lock().unwrap();
+                    state.pending -= 1;
+                    if state.pending == 0 {
+                        self.done.notify_all();
+                    }
+                }
+                None => state = self.work.wait(state).unwrap(),
+            }
+        }
+    }
+}
+
+/// Recursively copy the contents of `src` into the existing directory `dst`.
+pub(crate) fn copy_tree(src: &Path, dst: &Path, mode: CopyMode) -> io::Result<()> {
+    for entry in fs::read_dir(src).with_err_path(Operation::Copy, || src)? {
+        let entry = entry.with_err_path(Operation::Copy, || src)?;
+        let from = entry.path();
+        let to = dst.join(entry.file_name());
+        let file_type = entry.file_type().with_err_path(Operation::Copy, || &from)?;
+
+        if file_type.is_dir() {
+            fs::create_dir(&to).with_err_path(Operation::Create, || &to)?;
+            copy_tree(&from, &to, mode)?;
+            // Set the permissions last, so that read-only directories can
+            // still be populated.
+            let mut permissions = entry
+                .metadata()
+                .with_err_path(Operation::Copy, || &from)?
+                .permissions();
+            make_removable(&mut permissions);
+            fs::set_permissions(&to, permissions).with_err_path(Operation::Copy, || &to)?;
+        } else if file_type.is_symlink() {
+            let target = fs::read_link(&from).with_err_path(Operation::Copy, || &from)?;
+            crate::link::symlink(&target, &to).with_err_path(Operation::Create, || &to)?;
+        } else if file_type.is_file() {
+            match mode {
+                CopyMode::Copy => crate::file::copy_file(&from, &to)?,
+                CopyMode::Hardlink => match fs::hard_link(&from, &to) {
+                    Ok(()) => {}
+                    // Hard links can't cross filesystems, copy instead.
+                    Err(_) if !crate::file::same_filesystem(&from, dst).unwrap_or(true) => {
+                        crate::file::copy_file(&from, &to)?
+                    }
+                    Err(e) => return Err(e).with_err_path(Operation::Create, || &to),
+                },
+                CopyMode::Reflink => crate::file::reflink_file(&from, &to)?,
+            }
+        } else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot copy special files",
+            ))
+            .with_err_path(Operation::Copy, || &from);
+        }
+    }
+    Ok(())
+}
+
+/// Makes sure that the owner can remove the contents of a directory with
+/// these permissions, so that read-only directories don't prevent the
+/// temporary directory from being cleaned up.
+#[cfg(unix)]
+fn make_removable(permissions: &mut fs::Permissions) {
+    use std::os::unix::fs::PermissionsExt;
+    permissions.set_mode(permissions.mode() | 0o700);
+}
+
+#[cfg(not(unix))]
+fn make_removable(permissions: &mut fs::Permissions) {
+    permissions.set_readonly(false);
+}
@@ -1,45 +1,210 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::{error, fmt, io};
 
+/// The operation that failed, as reported by [`Error::operation`].
+///
+/// [`Error::operation`]: struct.Error.html#method.operation
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum Operation {
+    /// Creating a temporary file, directory or link.
+    Create,
+    /// Persisting a temporary file to its final path.
+    Persist,
+    /// Removing a temporary file or directory.
+    Remove,
+    /// Reopening a named temporary file.
+    Reopen,
+    /// Reading from a named temporary file.
+    Read,
+    /// Writing to or flushing a named temporary file.
+    Write,
+    /// Seeking within a named temporary file.
+    Seek,
+    /// Copying into a temporary file or directory.
+    Copy,
+    /// Allocating space for a temporary file.
+    Allocate,
+    /// Punching a hole in a temporary file.
+    PunchHole,
+    /// Reading the metadata of a path, such as the one passed to
+    /// `TempRoot::same_filesystem_as`.
+    Metadata,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match *self {
+            Operation::Create => "create",
+            Operation::Persist => "persist",
+            Operation::Remove => "remove",
+            Operation::Reopen => "reopen",
+            Operation::Read => "read",
+            Operation::Write => "write",
+            Operation::Seek => "seek",
+            Operation::Copy => "copy",
+            Operation::Allocate => "allocate",
+            Operation::PunchHole => "punch a hole in",
+            Operation::Metadata => "read the metadata of",
+        })
+    }
+}
+
+/// An IO error annotated with the path and operation it occurred on.
+///
+/// The functions in this crate return `std::io::Error`s, so that they can be
+/// used with `?` in functions returning `io::Result`. When the failure can be
+/// attributed to a path, the returned `io::Error` has the same [`kind`] as
+/// the original error and wraps an `Error`, which can be recovered with
+/// [`Error::from_io_error`] (or, equivalently, by downcasting
+/// `io::Error::get_ref`).
+///
+/// Persisting is the exception: [`PersistError`] and [`PathPersistError`]
+/// already record the paths involved, so their `error` is the unwrapped OS
+/// error, and `raw_os_error` keeps working on it. Converting them into an
+/// `io::Error` wraps that error in an `Error` with [`Operation::Persist`],
+/// the path of the temporary file and the [`destination`].
+///
+/// An `Error` displays the failed operation and the path followed by the
+/// underlying IO error, as `io::Error`'s own `Display` never looks at
+/// `source()`. The IO error is also returned from `source()`, for error
+/// reporters that walk the chain of sources (such as `anyhow` and `eyre`).
+///
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// # fn main() {
+/// #     if let Err(_) = run() {
+/// #         ::std::process::exit(1);
+/// #     }
+/// # }
+/// # fn run() -> Result<(), io::Error> {
+/// use tempfile::{Builder, Operation};
+///
+/// let err = Builder::new()
+///     .tempfile_in("/this/directory/does/not/exist")
+///     .unwrap_err();
+/// let err = tempfile::Error::from_io_error(&err).unwrap();
+/// assert_eq!(err.operation(), Operation::Create);
+/// assert!(err.path().starts_with("/this/directory/does/not/exist"));
+/// assert_eq!(err.kind(), io::ErrorKind::NotFound);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`kind`]: #method.kind
+/// [`Error::from_io_error`]: #method.from_io_error
+/// [`PersistError`]: struct.PersistError.html
+/// [`PathPersistError`]: struct.PathPersistError.html
+/// [`Operation::Persist`]: enum.Operation.html#variant.Persist
+/// [`destination`]: #method.destination
 #[derive(Debug)]
-struct PathError {
+pub struct Error {
+    operation: Operation,
     path: PathBuf,
+    destination: Option<PathBuf>,
     err: io::Error,
 }
 
-impl fmt::Display for PathError {
+impl Error {
+    /// Returns the `Error` wrapped by an `io::Error` returned from this
+    /// crate, if there is one.
+    pub fn from_io_error(err: &io::Error) -> Option<&Error> {
+        err.get_ref().and_then(|e| e.downcast_ref())
+    }
+
+    /// Returns the operation that failed.
+    pub fn operation(&self) -> Operation {
+        self.operation
+    }
+
+    /// Returns the path the operation failed on.
+    ///
+    /// When persisting, this is the path of the temporary file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the path a temporary file was being persisted to, if the
+    /// failed operation was [`Operation::Persist`].
+    ///
+    /// [`Operation::Persist`]: enum.Operation.html#variant.Persist
+    pub fn destination(&self) -> Option<&Path> {
+        self.destination.as_deref()
+    }
+
+    /// Returns the kind of the underlying IO error.
+    pub fn kind(&self) -> io::ErrorKind {
+        self.err.kind()
+    }
+
+    /// Returns a reference to the underlying IO error.
+    pub fn io_error(&self) -> &io::Error {
+        &self.err
+    }
+
+    /// Returns the underlying IO error.
+    pub fn into_io_error(self) -> io::Error {
+        self.err
+    }
+}
+
+impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} at path {:?}", self.err, self.path)
+        write!(f, "failed to {} {:?}", self.operation, self.path)?;
+        if let Some(ref destination) = self.destination {
+            write!(f, " to {:?}", destination)?;
+        }
+        write!(f, ": {}", self.err)
     }
 }
 
-impl error::Error for PathError {
+impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
-        self.err.source()
+        Some(&self.err)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> io::Error {
+        io::Error::new(err.kind(), err)
     }
 }
 
 pub(crate) trait IoResultExt<T> {
-    fn with_err_path<F, P>(self, path: F) -> Self
+    fn with_err_path<F, P>(self, operation: Operation, path: F) -> Self
     where
         F: FnOnce() -> P,
         P: Into<PathBuf>;
 }
 
 impl<T> IoResultExt<T> for Result<T, io::Error> {
-    fn with_err_path<F, P>(self, path: F) -> Self
+    fn with_err_path<F, P>(self, operation: Operation, path: F) -> Self
     where
         F: FnOnce() -> P,
         P: Into<PathBuf>,
     {
         self.map_err(|e| {
-            io::Error::new(
-                e.kind(),
-                PathError {
-                    path: path().into(),
-                    err: e,
-                },
-            )
+            Error {
+                operation,
+                path: path().into(),
+                destination: None,
+                err: e,
+            }
+            .into()
         })
     }
 }
+
+/// Wraps an error from persisting the temporary file at `path` to
+/// `destination`.
+pub(crate) fn persist_error(err: io::Error, path: &Path, destination: &Path) -> io::Error {
+    Error {
+        operation: Operation::Persist,
+        path: path.to_path_buf(),
+        destination: Some(destination.to_path_buf()),
+        err,
+    }
+    .into()
+}
@@ -1,7 +1,9 @@
-use std::fs::{File, OpenOptions};
+use std::fs::{self, File, OpenOptions};
 use std::io;
 use std::path::Path;
 
+use crate::{Builder, TempFileStrategy};
+
 fn not_supported<T>() -> io::Result<T> {
     Err(io::Error::new(
         io::ErrorKind::Other,
@@ -9,11 +11,15 @@ fn not_supported<T>() -> io::Result<T> {
     ))
 }
 
-pub fn create_named(_path: &Path, open_options: &mut OpenOptions) -> io::Result<File> {
+pub fn create_named(
+    _path: &Path,
+    _open_options: &mut OpenOptions,
+    _permissions: Option<&fs::Permissions>,
+) -> io::Result<File> {
     not_supported()
 }
 
-pub fn create(_dir: &Path) -> io::Result<File> {
+pub fn create(_dir: &Path, _builder: &Builder<'_, '_>) -> io::Result<(File, TempFileStrategy)> {
     not_supported()
 }
 
@@ -28,3 +34,43 @@ pub fn persist(_old_path: &Path, _new_path: &Path, _overwrite: bool) -> io::Resu
 pub fn keep(path: &Path) -> io::Result<()> {
     not_supported()
 }
+
+pub fn copy(_from: &File, _to: &File) -> io::Result<()> {
+    not_supported()
+}
+
+pub fn reflink(_from: &File, _to: &File) -> io::Result<()> {
+    not_supported()
+}
+
+pub fn allocate(_file: &File, _len: u64) -> io::Result<()> {
+    not_supported()
+}
+
+pub fn punch_hole(_file: &File, _offset: u64, _len: u64) -> io::Result<()> {
+    not_supported()
+}
+
+pub fn seek_data(_file: &File, _offset: u64) -> io::Result<Option<u64>> {
+    not_supported()
+}
+
+pub fn seek_hole(_file: &File, _offset: u64) -> io::Result<Option<u64>> {
+    not_supported()
+}
+
+pub fn free_space(_dir: &Path) -> io::Result<u64> {
+    not_supported()
+}
+
+pub fn is_tmpfs(_dir: &Path) -> io::Result<bool> {
+    not_supported()
+}
+
+pub fn supports_tmpfile(_dir: &Path) -> bool {
+    false
+}
+
+pub fn same_filesystem(_a: &Path, _b: &Path) -> io::Result<bool> {
+    not_supported()
+}
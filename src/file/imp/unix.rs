@@ -1,5 +1,4 @@
 use std::env;
-use std::ffi::OsStr;
 use std::fs::{self, File, OpenOptions};
 use std::io;
 cfg_if::cfg_if! {
@@ -11,23 +10,30 @@ cfg_if::cfg_if! {
     }
 }
 use crate::util;
+use crate::{Builder, TempFileStrategy};
 use std::path::Path;
 
 #[cfg(not(target_os = "redox"))]
 use rustix::fs::{cwd, linkat, renameat, unlinkat, AtFlags};
 
-pub fn create_named(path: &Path, open_options: &mut OpenOptions) -> io::Result<File> {
+#[cfg_attr(target_os = "wasi", allow(unused_variables))]
+pub fn create_named(
+    path: &Path,
+    open_options: &mut OpenOptions,
+    permissions: Option<&fs::Permissions>,
+) -> io::Result<File> {
     open_options.read(true).write(true).create_new(true);
 
     #[cfg(not(target_os = "wasi"))]
     {
-        open_options.mode(0o600);
+        use std::os::unix::fs::PermissionsExt;
+        open_options.mode(permissions.map_or(0o600, |p| p.mode()));
     }
 
     open_options.open(path)
 }
 
-fn create_unlinked(path: &Path) -> io::Result<File> {
+fn create_unlinked(path: &Path, builder: &Builder<'_, '_>) -> io::Result<File> {
     let tmp;
     // shadow this to decrease the lifetime. It can't live longer than `tmp`.
     let mut path = path;
@@ -37,7 +43,11 @@ fn create_unlinked(path: &Path) -> io::Result<File> {
         path = &tmp;
     }
 
-    let f = create_named(path, &mut OpenOptions::new())?;
+    let f = create_named(
+        path,
+        &mut builder.open_options(),
+        builder.permissions.as_ref(),
+    )?;
     // don't care whether the path has already been unlinked,
     // but perhaps there are some IO error conditions we should send up?
     let _ = fs::remove_file(path);
@@ -45,37 +55,65 @@ fn create_unlinked(path: &Path) -> io::Result<File> {
 }
 
 #[cfg(target_os = "linux")]
-pub fn create(dir: &Path) -> io::Result<File> {
+pub fn create(dir: &Path, builder: &Builder<'_, '_>) -> io::Result<(File, TempFileStrategy)> {
     use rustix::{fs::OFlags, io::Errno};
-    OpenOptions::new()
-        .read(true)
-        .write(true)
-        .custom_flags(OFlags::TMPFILE.bits() as i32) // do not mix with `create_new(true)`
-        .open(dir)
-        .or_else(|e| {
+    use std::os::unix::fs::PermissionsExt;
+    let create_tmpfile = || {
+        let mut open_options = builder.open_options();
+        // Without explicit permissions, keep the default mode (`0o666`, less
+        // the umask), which only matters if the file is linked in later.
+        if let Some(ref permissions) = builder.permissions {
+            open_options.mode(permissions.mode());
+        }
+        open_options
+            .read(true)
+            .write(true)
+            // do not mix with `create_new(true)`
+            .custom_flags(builder.custom_flags | OFlags::TMPFILE.bits() as i32)
+            .open(dir)
+            .map(|file| (file, TempFileStrategy::OTmpFile))
+    };
+    match builder.strategy {
+        None => create_tmpfile().or_else(|e| {
             match Errno::from_io_error(&e) {
                 // These are the three "not supported" error codes for O_TMPFILE.
                 Some(Errno::OPNOTSUPP) | Some(Errno::ISDIR) | Some(Errno::NOENT) => {
-                    create_unix(dir)
+                    create_unix(dir, builder)
                 }
                 _ => Err(e),
             }
-        })
+        }),
+        Some(TempFileStrategy::OTmpFile) => create_tmpfile(),
+        Some(TempFileStrategy::CreateUnlink) => create_unix(dir, builder),
+        Some(_) => not_supported(),
+    }
 }
 
 #[cfg(not(target_os = "linux"))]
-pub fn create(dir: &Path) -> io::Result<File> {
-    create_unix(dir)
+pub fn create(dir: &Path, builder: &Builder<'_, '_>) -> io::Result<(File, TempFileStrategy)> {
+    match builder.strategy {
+        None | Some(TempFileStrategy::CreateUnlink) => create_unix(dir, builder),
+        Some(_) => not_supported(),
+    }
+}
+
+#[allow(clippy::io_other_error)]
+fn not_supported<T>() -> io::Result<T> {
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        "temporary file strategy not supported on this platform",
+    ))
 }
 
-fn create_unix(dir: &Path) -> io::Result<File> {
+fn create_unix(dir: &Path, builder: &Builder<'_, '_>) -> io::Result<(File, TempFileStrategy)> {
     util::create_helper(
         dir,
-        OsStr::new(".tmp"),
-        OsStr::new(""),
-        crate::NUM_RAND_CHARS,
-        |path| create_unlinked(&path),
+        builder.prefix,
+        builder.suffix,
+        builder.random_len,
+        |path| create_unlinked(&path, builder),
     )
+    .map(|file| (file, TempFileStrategy::CreateUnlink))
 }
 
 #[cfg(any(not(target_os = "wasi"), feature = "nightly"))]
@@ -143,3 +181,217 @@ pub fn persist(old_path: &Path, new_path: &Path, overwrite: bool) -> io::Result<
 pub fn keep(_: &Path) -> io::Result<()> {
     Ok(())
 }
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn copy(from: &File, to: &File) -> io::Result<()> {
+    use rustix::fs::copy_file_range;
+    use rustix::io::Errno;
+
+    // Try a reflink first. It fails without modifying `to` if it isn't
+    // supported, e.g. with `EOPNOTSUPP` or `EXDEV`, or if `to` was opened in
+    // append mode, with `EBADF`.
+    if reflink(from, to).is_ok() {
+        return Ok(());
+    }
+
+    // Then copy in the kernel, and finally in userspace.
+    let mut copied = 0;
+    loop {
+        match copy_file_range(from, None, to, None, 1 << 30) {
+            Ok(0) => return Ok(()),
+            Ok(n) => copied += n,
+            // Fall back to a userspace copy if the kernel or filesystem
+            // doesn't support it, or `to` is in append mode (`EBADF`), as
+            // long as nothing was copied yet.
+            Err(Errno::NOSYS)
+            | Err(Errno::BADF)
+            | Err(Errno::XDEV)
+            | Err(Errno::INVAL)
+            | Err(Errno::OPNOTSUPP)
+            | Err(Errno::PERM)
+                if copied == 0 =>
+            {
+                io::copy(&mut &*from, &mut &*to)?;
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+pub fn copy(from: &File, to: &File) -> io::Result<()> {
+    io::copy(&mut &*from, &mut &*to).map(|_| ())
+}
+
+/// Shares the extents of `from` with `to`, on filesystems that support it,
+/// such as Btrfs and XFS.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn reflink(from: &File, to: &File) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    // SAFETY: both file descriptors are valid for the duration of the call,
+    // and `FICLONE` takes the source file descriptor as its argument.
+    if unsafe { libc::ioctl(to.as_raw_fd(), libc::FICLONE, from.as_raw_fd()) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+pub fn reflink(_from: &File, _to: &File) -> io::Result<()> {
+    not_supported()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn allocate(file: &File, len: u64) -> io::Result<()> {
+    use rustix::fs::{fallocate, FallocateFlags};
+    use rustix::io::Errno;
+
+    // `fallocate` fails with `EINVAL` for an empty range.
+    if len == 0 {
+        return Ok(());
+    }
+    match fallocate(file, FallocateFlags::empty(), 0, len) {
+        Ok(()) => Ok(()),
+        Err(Errno::OPNOTSUPP) | Err(Errno::NOSYS) => extend(file, len),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+pub fn allocate(file: &File, len: u64) -> io::Result<()> {
+    extend(file, len)
+}
+
+fn extend(file: &File, len: u64) -> io::Result<()> {
+    if file.metadata()?.len() < len {
+        file.set_len(len)?;
+    }
+    Ok(())
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn punch_hole(file: &File, offset: u64, len: u64) -> io::Result<()> {
+    use rustix::fs::{fallocate, FallocateFlags};
+    Ok(fallocate(
+        file,
+        FallocateFlags::PUNCH_HOLE | FallocateFlags::KEEP_SIZE,
+        offset,
+        len,
+    )?)
+}
+
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+pub fn punch_hole(_file: &File, _offset: u64, _len: u64) -> io::Result<()> {
+    not_supported()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn lseek(file: &File, offset: u64, whence: i32) -> io::Result<Option<u64>> {
+    use std::os::unix::io::AsRawFd;
+
+    let offset = offset as libc::off64_t;
+    // SAFETY: `lseek64` takes no pointers, and the descriptor is valid for as
+    // long as `file` is borrowed.
+    match unsafe { libc::lseek64(file.as_raw_fd(), offset, whence) } {
+        -1 => {
+            let err = io::Error::last_os_error();
+            // There's no data (or hole) at or after `offset`.
+            if err.raw_os_error() == Some(libc::ENXIO) {
+                Ok(None)
+            } else {
+                Err(err)
+            }
+        }
+        pos => Ok(Some(pos as u64)),
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn seek_data(file: &File, offset: u64) -> io::Result<Option<u64>> {
+    lseek(file, offset, libc::SEEK_DATA)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub fn seek_hole(file: &File, offset: u64) -> io::Result<Option<u64>> {
+    lseek(file, offset, libc::SEEK_HOLE)
+}
+
+// Without `SEEK_DATA` and `SEEK_HOLE`, the whole file is data, followed by
+// the implicit hole at the end of the file.
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+pub fn seek_data(file: &File, offset: u64) -> io::Result<Option<u64>> {
+    let len = file.metadata()?.len();
+    Ok(if offset < len { Some(offset) } else { None })
+}
+
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+pub fn seek_hole(file: &File, offset: u64) -> io::Result<Option<u64>> {
+    let len = file.metadata()?.len();
+    Ok(if offset < len { Some(len) } else { None })
+}
+
+#[cfg(not(any(
+    target_os = "haiku",
+    target_os = "illumos",
+    target_os = "redox",
+    target_os = "solaris",
+    target_os = "wasi",
+)))]
+#[allow(clippy::unnecessary_cast)] // The field types vary between platforms.
+pub fn free_space(dir: &Path) -> io::Result<u64> {
+    let stat = rustix::fs::statvfs(dir)?;
+    Ok((stat.f_bavail as u64).saturating_mul(stat.f_frsize as u64))
+}
+
+#[cfg(any(
+    target_os = "haiku",
+    target_os = "illumos",
+    target_os = "redox",
+    target_os = "solaris",
+    target_os = "wasi",
+))]
+pub fn free_space(_dir: &Path) -> io::Result<u64> {
+    not_supported()
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[allow(clippy::unnecessary_cast)] // The field types vary between platforms.
+pub fn is_tmpfs(dir: &Path) -> io::Result<bool> {
+    let stat = rustix::fs::statfs(dir)?;
+    Ok(stat.f_type as i64 == libc::TMPFS_MAGIC as i64)
+}
+
+#[cfg(not(any(target_os = "android", target_os = "linux")))]
+pub fn is_tmpfs(_dir: &Path) -> io::Result<bool> {
+    Ok(false)
+}
+
+#[cfg(target_os = "linux")]
+pub fn supports_tmpfile(dir: &Path) -> bool {
+    use rustix::fs::OFlags;
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .mode(0o600)
+        .custom_flags(OFlags::TMPFILE.bits() as i32)
+        .open(dir)
+        .is_ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn supports_tmpfile(_dir: &Path) -> bool {
+    false
+}
+
+#[cfg(any(not(target_os = "wasi"), feature = "nightly"))]
+pub fn same_filesystem(a: &Path, b: &Path) -> io::Result<bool> {
+    Ok(fs::metadata(a)?.dev() == fs::metadata(b)?.dev())
+}
+
+#[cfg(all(target_os = "wasi", not(feature = "nightly")))]
+pub fn same_filesystem(_a: &Path, _b: &Path) -> io::Result<bool> {
+    not_supported()
+}
@@ -1,49 +1,69 @@
-use std::ffi::OsStr;
-use std::fs::{File, OpenOptions};
+use std::fs::{self, File, OpenOptions};
 use std::os::windows::ffi::OsStrExt;
 use std::os::windows::fs::OpenOptionsExt;
 use std::os::windows::io::{AsRawHandle, FromRawHandle, RawHandle};
 use std::path::Path;
-use std::{io, iter};
+use std::{io, iter, ptr};
 
 use windows_sys::Win32::Foundation::{HANDLE, INVALID_HANDLE_VALUE};
 use windows_sys::Win32::Storage::FileSystem::{
-    MoveFileExW, ReOpenFile, SetFileAttributesW, FILE_ATTRIBUTE_NORMAL, FILE_ATTRIBUTE_TEMPORARY,
-    FILE_FLAG_DELETE_ON_CLOSE, FILE_GENERIC_READ, FILE_GENERIC_WRITE, FILE_SHARE_DELETE,
-    FILE_SHARE_READ, FILE_SHARE_WRITE, MOVEFILE_REPLACE_EXISTING,
+    GetDiskFreeSpaceExW, GetVolumePathNameW, MoveFileExW, ReOpenFile, SetFileAttributesW,
+    FILE_ATTRIBUTE_NORMAL, FILE_ATTRIBUTE_TEMPORARY, FILE_FLAG_DELETE_ON_CLOSE, FILE_GENERIC_READ,
+    FILE_GENERIC_WRITE, FILE_SHARE_DELETE, FILE_SHARE_READ, FILE_SHARE_WRITE,
+    MOVEFILE_REPLACE_EXISTING,
 };
 
 use crate::util;
+use crate::{Builder, TempFileStrategy};
 
 fn to_utf16(s: &Path) -> Vec<u16> {
     s.as_os_str().encode_wide().chain(iter::once(0)).collect()
 }
 
-pub fn create_named(path: &Path, open_options: &mut OpenOptions) -> io::Result<File> {
-    open_options
+pub fn create_named(
+    path: &Path,
+    open_options: &mut OpenOptions,
+    permissions: Option<&fs::Permissions>,
+) -> io::Result<File> {
+    let file = open_options
         .create_new(true)
         .read(true)
         .write(true)
         .custom_flags(FILE_ATTRIBUTE_TEMPORARY)
-        .open(path)
+        .open(path)?;
+    if let Some(permissions) = permissions {
+        file.set_permissions(permissions.clone())?;
+    }
+    Ok(file)
 }
 
-pub fn create(dir: &Path) -> io::Result<File> {
+pub fn create(dir: &Path, builder: &Builder<'_, '_>) -> io::Result<(File, TempFileStrategy)> {
+    match builder.strategy {
+        None | Some(TempFileStrategy::DeleteOnClose) => {}
+        Some(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "temporary file strategy not supported on this platform",
+            ))
+        }
+    }
     util::create_helper(
         dir,
-        OsStr::new(".tmp"),
-        OsStr::new(""),
-        crate::NUM_RAND_CHARS,
+        builder.prefix,
+        builder.suffix,
+        builder.random_len,
         |path| {
             OpenOptions::new()
                 .create_new(true)
                 .read(true)
                 .write(true)
+                .append(builder.append)
                 .share_mode(0)
                 .custom_flags(FILE_ATTRIBUTE_TEMPORARY | FILE_FLAG_DELETE_ON_CLOSE)
                 .open(path)
         },
     )
+    .map(|file| (file, TempFileStrategy::DeleteOnClose))
 }
 
 pub fn reopen(file: &File, _path: &Path) -> io::Result<File> {
@@ -105,3 +125,79 @@ pub fn persist(old_path: &Path, new_path: &Path, overwrite: bool) -> io::Result<
         }
     }
 }
+
+pub fn copy(from: &File, to: &File) -> io::Result<()> {
+    io::copy(&mut &*from, &mut &*to).map(|_| ())
+}
+
+pub fn reflink(_from: &File, _to: &File) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        "operation not supported on this platform",
+    ))
+}
+
+pub fn allocate(file: &File, len: u64) -> io::Result<()> {
+    if file.metadata()?.len() < len {
+        file.set_len(len)?;
+    }
+    Ok(())
+}
+
+pub fn punch_hole(_file: &File, _offset: u64, _len: u64) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        "operation not supported on this platform",
+    ))
+}
+
+pub fn seek_data(file: &File, offset: u64) -> io::Result<Option<u64>> {
+    let len = file.metadata()?.len();
+    Ok(if offset < len { Some(offset) } else { None })
+}
+
+pub fn seek_hole(file: &File, offset: u64) -> io::Result<Option<u64>> {
+    let len = file.metadata()?.len();
+    Ok(if offset < len { Some(len) } else { None })
+}
+
+pub fn free_space(dir: &Path) -> io::Result<u64> {
+    let dir = to_utf16(dir);
+    let mut available = 0;
+    unsafe {
+        let ret = GetDiskFreeSpaceExW(
+            dir.as_ptr(),
+            &mut available,
+            ptr::null_mut(),
+            ptr::null_mut(),
+        );
+        if ret == 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(available)
+}
+
+pub fn is_tmpfs(_dir: &Path) -> io::Result<bool> {
+    Ok(false)
+}
+
+pub fn supports_tmpfile(_dir: &Path) -> bool {
+    false
+}
+
+fn volume_path(path: &Path) -> io::Result<String> {
+    let path = to_utf16(path);
+    let mut volume = [0u16; 261];
+    unsafe {
+        if GetVolumePathNameW(path.as_ptr(), volume.as_mut_ptr(), volume.len() as u32) == 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    let len = volume.iter().position(|&c| c == 0).unwrap_or(volume.len());
+    Ok(String::from_utf16_lossy(&volume[..len]).to_lowercase())
+}
+
+pub fn same_filesystem(a: &Path, b: &Path) -> io::Result<bool> {
+    Ok(volume_path(a)? == volume_path(b)?)
+}
@@ -1,18 +1,20 @@
 use std::env;
 use std::error;
-use std::ffi::OsStr;
+use std::ffi::{OsStr, OsString};
 use std::fmt;
 use std::fs::{self, File, OpenOptions};
 use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::mem;
-use std::ops::Deref;
+use std::ops::{Deref, Range};
 use std::path::{Path, PathBuf};
 
-use crate::error::IoResultExt;
+use crate::error::{persist_error, IoResultExt, Operation};
 use crate::Builder;
 
 mod imp;
 
+pub(crate) use self::imp::{free_space, is_tmpfs, same_filesystem, supports_tmpfile};
+
 /// Create a new temporary file.
 ///
 /// The file will be created in the location returned by [`std::env::temp_dir()`].
@@ -52,7 +54,7 @@ mod imp;
 ///
 /// [`std::env::temp_dir()`]: https://doc.rust-lang.org/std/env/fn.temp_dir.html
 pub fn tempfile() -> io::Result<File> {
-    tempfile_in(&env::temp_dir())
+    tempfile_in(env::temp_dir())
 }
 
 /// Create a new temporary file in the specified directory.
@@ -93,7 +95,76 @@ pub fn tempfile() -> io::Result<File> {
 ///
 /// [`std::env::temp_dir()`]: https://doc.rust-lang.org/std/env/fn.temp_dir.html
 pub fn tempfile_in<P: AsRef<Path>>(dir: P) -> io::Result<File> {
-    imp::create(dir.as_ref())
+    imp::create(dir.as_ref(), &Builder::new()).map(|(file, _)| file)
+}
+
+/// Create a new temporary file, and report how it was created.
+///
+/// This is the same as [`tempfile()`], except that it also returns the
+/// [`TempFileStrategy`] that was used. Use [`Builder::strategy`] to require a
+/// specific strategy.
+///
+/// # Examples
+///
+/// ```
+/// use tempfile::{tempfile_with_info, TempFileStrategy};
+///
+/// # use std::io;
+/// # fn main() {
+/// #     if let Err(_) = run() {
+/// #         ::std::process::exit(1);
+/// #     }
+/// # }
+/// # fn run() -> Result<(), io::Error> {
+/// let (file, strategy) = tempfile_with_info()?;
+/// if strategy != TempFileStrategy::OTmpFile {
+///     eprintln!("temporary file was briefly visible: {:?}", strategy);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`tempfile()`]: fn.tempfile.html
+/// [`TempFileStrategy`]: enum.TempFileStrategy.html
+/// [`Builder::strategy`]: struct.Builder.html#method.strategy
+pub fn tempfile_with_info() -> io::Result<(File, TempFileStrategy)> {
+    tempfile_in_with_info(env::temp_dir())
+}
+
+/// Create a new temporary file in the specified directory, and report how it
+/// was created.
+///
+/// See [`tempfile_with_info()`] for details.
+///
+/// [`tempfile_with_info()`]: fn.tempfile_with_info.html
+pub fn tempfile_in_with_info<P: AsRef<Path>>(dir: P) -> io::Result<(File, TempFileStrategy)> {
+    imp::create(dir.as_ref(), &Builder::new())
+}
+
+/// The way an unnamed temporary file was, or should be, created.
+///
+/// See [`tempfile_with_info()`] and [`Builder::strategy`].
+///
+/// [`tempfile_with_info()`]: fn.tempfile_with_info.html
+/// [`Builder::strategy`]: struct.Builder.html#method.strategy
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum TempFileStrategy {
+    /// The file was opened with `O_TMPFILE`, so it never had a name.
+    ///
+    /// Only supported on Linux, and only by some filesystems.
+    OTmpFile,
+    /// The file was created with a random name and immediately unlinked.
+    ///
+    /// The file is briefly visible in the directory, and may be left behind
+    /// if the process is killed in between.
+    CreateUnlink,
+    /// The file was created with a random name and marked to be deleted once
+    /// closed.
+    ///
+    /// Only used on Windows. The file stays visible in the directory until
+    /// it's closed.
+    DeleteOnClose,
 }
 
 /// Error returned when persisting a temporary file path fails.
@@ -103,12 +174,15 @@ pub struct PathPersistError {
     pub error: io::Error,
     /// The temporary file path that couldn't be persisted.
     pub path: TempPath,
+    /// The path the temporary file was being persisted to. When keeping the
+    /// temporary file, this is the path of the temporary file itself.
+    pub destination: PathBuf,
 }
 
 impl From<PathPersistError> for io::Error {
     #[inline]
     fn from(error: PathPersistError) -> io::Error {
-        error.error
+        persist_error(error.error, &error.path, &error.destination)
     }
 }
 
@@ -121,7 +195,11 @@ impl From<PathPersistError> for TempPath {
 
 impl fmt::Display for PathPersistError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "failed to persist temporary file path: {}", self.error)
+        write!(
+            f,
+            "failed to persist temporary file path {:?} to {:?}: {}",
+            self.path, self.destination, self.error
+        )
     }
 }
 
@@ -176,7 +254,7 @@ impl TempPath {
     /// # }
     /// ```
     pub fn close(mut self) -> io::Result<()> {
-        let result = fs::remove_file(&self.path).with_err_path(|| &*self.path);
+        let result = fs::remove_file(&self.path).with_err_path(Operation::Remove, || &*self.path);
         self.path = PathBuf::new().into_boxed_path();
         mem::forget(self);
         result
@@ -226,7 +304,8 @@ impl TempPath {
     ///
     /// [`PathPersistError`]: struct.PathPersistError.html
     pub fn persist<P: AsRef<Path>>(mut self, new_path: P) -> Result<(), PathPersistError> {
-        match imp::persist(&self.path, new_path.as_ref(), true) {
+        let new_path = new_path.as_ref();
+        match imp::persist(&self.path, new_path, true) {
             Ok(_) => {
                 // Don't drop `self`. We don't want to try deleting the old
                 // temporary file path. (It'll fail, but the failure is never
@@ -237,6 +316,7 @@ impl TempPath {
             }
             Err(e) => Err(PathPersistError {
                 error: e,
+                destination: new_path.to_path_buf(),
                 path: self,
             }),
         }
@@ -288,7 +368,8 @@ impl TempPath {
         mut self,
         new_path: P,
     ) -> Result<(), PathPersistError> {
-        match imp::persist(&self.path, new_path.as_ref(), false) {
+        let new_path = new_path.as_ref();
+        match imp::persist(&self.path, new_path, false) {
             Ok(_) => {
                 // Don't drop `self`. We don't want to try deleting the old
                 // temporary file path. (It'll fail, but the failure is never
@@ -299,6 +380,7 @@ impl TempPath {
             }
             Err(e) => Err(PathPersistError {
                 error: e,
+                destination: new_path.to_path_buf(),
                 path: self,
             }),
         }
@@ -347,6 +429,7 @@ impl TempPath {
             }
             Err(e) => Err(PathPersistError {
                 error: e,
+                destination: self.to_path_buf(),
                 path: self,
             }),
         }
@@ -504,6 +587,9 @@ pub struct PersistError<F = File> {
     pub error: io::Error,
     /// The temporary file that couldn't be persisted.
     pub file: NamedTempFile<F>,
+    /// The path the temporary file was being persisted to. When keeping the
+    /// temporary file, this is the path of the temporary file itself.
+    pub destination: PathBuf,
 }
 
 impl<F> fmt::Debug for PersistError<F> {
@@ -515,7 +601,7 @@ impl<F> fmt::Debug for PersistError<F> {
 impl<F> From<PersistError<F>> for io::Error {
     #[inline]
     fn from(error: PersistError<F>) -> io::Error {
-        error.error
+        persist_error(error.error, error.file.path(), &error.destination)
     }
 }
 
@@ -528,7 +614,13 @@ impl<F> From<PersistError<F>> for NamedTempFile<F> {
 
 impl<F> fmt::Display for PersistError<F> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "failed to persist temporary file: {}", self.error)
+        write!(
+            f,
+            "failed to persist temporary file {:?} to {:?}: {}",
+            self.file.path(),
+            self.destination,
+            self.error
+        )
     }
 }
 
@@ -608,6 +700,26 @@ impl NamedTempFile<File> {
     pub fn new_in<P: AsRef<Path>>(dir: P) -> io::Result<NamedTempFile> {
         Builder::new().tempfile_in(dir)
     }
+
+    /// Create a new named temporary file next to `dest`, so that it can be
+    /// persisted to `dest`.
+    ///
+    /// See [`Builder::tempfile_for`] for details.
+    ///
+    /// [`Builder::tempfile_for`]: struct.Builder.html#method.tempfile_for
+    pub fn new_for<P: AsRef<Path>>(dest: P) -> io::Result<NamedTempFile> {
+        Builder::new().tempfile_for(dest)
+    }
+
+    /// Create a new named temporary file holding a copy of the file at `path`.
+    ///
+    /// The copy is created next to the original, so that it can cheaply be
+    /// persisted back over it. See [`Builder::copy_of`] for details.
+    ///
+    /// [`Builder::copy_of`]: struct.Builder.html#method.copy_of
+    pub fn clone_from<P: AsRef<Path>>(path: P) -> io::Result<NamedTempFile> {
+        Builder::new().copy_of(path)
+    }
 }
 
 impl<F> NamedTempFile<F> {
@@ -642,6 +754,44 @@ impl<F> NamedTempFile<F> {
         &self.path
     }
 
+    /// Get the temporary file's path as an argument for
+    /// [`std::process::Command::arg`].
+    ///
+    /// The path is always absolute, so it stays valid regardless of the child
+    /// process's working directory.
+    ///
+    /// # Security
+    ///
+    /// Referring to a temporary file's path may not be secure in all cases.
+    /// Please read the security section on the top level documentation of this
+    /// type for details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io::{self, Write};
+    /// use std::process::Command;
+    /// use tempfile::NamedTempFile;
+    ///
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// let mut file = NamedTempFile::new()?;
+    /// writeln!(file, "Brian was here. Briefly.")?;
+    ///
+    /// let status = Command::new("cat").arg(file.to_command_arg()).status()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`std::process::Command::arg`]: https://doc.rust-lang.org/std/process/struct.Command.html#method.arg
+    pub fn to_command_arg(&self) -> OsString {
+        self.path().as_os_str().to_owned()
+    }
+
     /// Close and remove the temporary file.
     ///
     /// Use this if you want to detect errors in deleting the file.
@@ -725,10 +875,15 @@ impl<F> NamedTempFile<F> {
         match path.persist(new_path) {
             Ok(_) => Ok(file),
             Err(err) => {
-                let PathPersistError { error, path } = err;
+                let PathPersistError {
+                    error,
+                    path,
+                    destination,
+                } = err;
                 Err(PersistError {
                     file: NamedTempFile { path, file },
                     error,
+                    destination,
                 })
             }
         }
@@ -778,10 +933,15 @@ impl<F> NamedTempFile<F> {
         match path.persist_noclobber(new_path) {
             Ok(_) => Ok(file),
             Err(err) => {
-                let PathPersistError { error, path } = err;
+                let PathPersistError {
+                    error,
+                    path,
+                    destination,
+                } = err;
                 Err(PersistError {
                     file: NamedTempFile { path, file },
                     error,
+                    destination,
                 })
             }
         }
@@ -821,9 +981,14 @@ impl<F> NamedTempFile<F> {
         let (file, path) = (self.file, self.path);
         match path.keep() {
             Ok(path) => Ok((file, path)),
-            Err(PathPersistError { error, path }) => Err(PersistError {
+            Err(PathPersistError {
+                error,
+                path,
+                destination,
+            }) => Err(PersistError {
                 file: NamedTempFile { path, file },
                 error,
+                destination,
             }),
         }
     }
@@ -908,13 +1073,155 @@ impl NamedTempFile<File> {
     /// ```
     pub fn reopen(&self) -> io::Result<File> {
         imp::reopen(self.as_file(), NamedTempFile::path(self))
-            .with_err_path(|| NamedTempFile::path(self))
+            .with_err_path(Operation::Reopen, || NamedTempFile::path(self))
+    }
+
+    /// Allocate disk space for the first `len` bytes of the temporary file.
+    ///
+    /// On Linux, this uses `fallocate`, so that running out of disk space is
+    /// reported now rather than on a later write, and the file is less likely
+    /// to be fragmented. Elsewhere, or if the filesystem doesn't support it,
+    /// this falls back to extending the file with `set_len`, which may not
+    /// actually reserve any space.
+    ///
+    /// The file is extended to `len` bytes if it's shorter, but is never
+    /// truncated. See also [`Builder::preallocate`].
+    ///
+    /// # Errors
+    ///
+    /// If the space can not be allocated, `Err` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io;
+    /// use tempfile::NamedTempFile;
+    ///
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// let file = NamedTempFile::new()?;
+    /// file.allocate(1 << 30)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Builder::preallocate`]: struct.Builder.html#method.preallocate
+    pub fn allocate(&self, len: u64) -> io::Result<()> {
+        imp::allocate(self.as_file(), len).with_err_path(Operation::Allocate, || self.path())
+    }
+
+    /// Deallocate the disk space backing `len` bytes of the temporary file,
+    /// starting at `offset`.
+    ///
+    /// The range reads back as zeros afterwards, and the length of the file
+    /// is unchanged. This uses `fallocate` with `FALLOC_FL_PUNCH_HOLE`, so
+    /// it's only supported on Linux, and only by some filesystems.
+    ///
+    /// # Errors
+    ///
+    /// If the hole can not be punched, `Err` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io;
+    /// use std::io::Write;
+    /// use tempfile::NamedTempFile;
+    ///
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// let mut file = NamedTempFile::new()?;
+    /// file.write_all(&[1; 1 << 20])?;
+    /// file.punch_hole(4096, 8192)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn punch_hole(&self, offset: u64, len: u64) -> io::Result<()> {
+        imp::punch_hole(self.as_file(), offset, len)
+            .with_err_path(Operation::PunchHole, || self.path())
+    }
+
+    /// Returns the offset of the first byte of data at or after `offset`,
+    /// skipping over holes, or `None` if there's no more data.
+    ///
+    /// This is `lseek` with `SEEK_DATA`, so it also moves the file cursor to
+    /// the returned offset. Where that isn't supported, the whole file is
+    /// treated as data.
+    pub fn seek_data(&self, offset: u64) -> io::Result<Option<u64>> {
+        imp::seek_data(self.as_file(), offset).with_err_path(Operation::Seek, || self.path())
+    }
+
+    /// Returns the offset of the first byte of the hole at or after `offset`,
+    /// or `None` if `offset` is past the end of the file.
+    ///
+    /// The end of the file counts as a hole. This is `lseek` with
+    /// `SEEK_HOLE`, so it also moves the file cursor to the returned offset.
+    /// Where that isn't supported, the whole file is treated as data.
+    pub fn seek_hole(&self, offset: u64) -> io::Result<Option<u64>> {
+        imp::seek_hole(self.as_file(), offset).with_err_path(Operation::Seek, || self.path())
+    }
+
+    /// Returns the ranges of the temporary file holding data, in order,
+    /// skipping over holes.
+    ///
+    /// The position of the file cursor is preserved.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io;
+    /// use tempfile::Builder;
+    ///
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// let file = Builder::new().sparse(1 << 30).tempfile()?;
+    /// for range in file.data_ranges()? {
+    ///     println!("data at {:?}", range);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn data_ranges(&self) -> io::Result<Vec<Range<u64>>> {
+        #[allow(clippy::seek_from_current)]
+        let pos = (&*self).seek(SeekFrom::Current(0))?;
+        // Restore the position even if looking for data or holes failed.
+        let ranges = self.find_data_ranges();
+        (&*self).seek(SeekFrom::Start(pos))?;
+        ranges
+    }
+
+    fn find_data_ranges(&self) -> io::Result<Vec<Range<u64>>> {
+        let mut ranges = Vec::new();
+        let mut offset = 0;
+        while let Some(start) = self.seek_data(offset)? {
+            let end = match self.seek_hole(start)? {
+                Some(end) => end,
+                None => break,
+            };
+            ranges.push(start..end);
+            offset = end;
+        }
+        Ok(ranges)
     }
 }
 
 impl<F: Read> Read for NamedTempFile<F> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        self.as_file_mut().read(buf).with_err_path(|| self.path())
+        self.as_file_mut()
+            .read(buf)
+            .with_err_path(Operation::Read, || self.path())
     }
 }
 
@@ -923,17 +1230,23 @@ where
     &'a F: Read,
 {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        self.as_file().read(buf).with_err_path(|| self.path())
+        self.as_file()
+            .read(buf)
+            .with_err_path(Operation::Read, || self.path())
     }
 }
 
 impl<F: Write> Write for NamedTempFile<F> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.as_file_mut().write(buf).with_err_path(|| self.path())
+        self.as_file_mut()
+            .write(buf)
+            .with_err_path(Operation::Write, || self.path())
     }
     #[inline]
     fn flush(&mut self) -> io::Result<()> {
-        self.as_file_mut().flush().with_err_path(|| self.path())
+        self.as_file_mut()
+            .flush()
+            .with_err_path(Operation::Write, || self.path())
     }
 }
 
@@ -942,17 +1255,23 @@ where
     &'a F: Write,
 {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.as_file().write(buf).with_err_path(|| self.path())
+        self.as_file()
+            .write(buf)
+            .with_err_path(Operation::Write, || self.path())
     }
     #[inline]
     fn flush(&mut self) -> io::Result<()> {
-        self.as_file().flush().with_err_path(|| self.path())
+        self.as_file()
+            .flush()
+            .with_err_path(Operation::Write, || self.path())
     }
 }
 
 impl<F: Seek> Seek for NamedTempFile<F> {
     fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
-        self.as_file_mut().seek(pos).with_err_path(|| self.path())
+        self.as_file_mut()
+            .seek(pos)
+            .with_err_path(Operation::Seek, || self.path())
     }
 }
 
@@ -961,7 +1280,9 @@ where
     &'a F: Seek,
 {
     fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
-        self.as_file().seek(pos).with_err_path(|| self.path())
+        self.as_file()
+            .seek(pos)
+            .with_err_path(Operation::Seek, || self.path())
     }
 }
 
@@ -989,19 +1310,102 @@ where
 
 pub(crate) fn create_named(
     mut path: PathBuf,
-    open_options: &mut OpenOptions,
+    builder: &Builder<'_, '_>,
 ) -> io::Result<NamedTempFile> {
     // Make the path absolute. Otherwise, changing directories could cause us to
     // delete the wrong file.
     if !path.is_absolute() {
         path = env::current_dir()?.join(path)
     }
-    imp::create_named(&path, open_options)
-        .with_err_path(|| path.clone())
-        .map(|file| NamedTempFile {
-            path: TempPath {
-                path: path.into_boxed_path(),
-            },
-            file,
-        })
+    let file = imp::create_named(
+        &path,
+        &mut builder.open_options(),
+        builder.permissions.as_ref(),
+    )
+    .with_err_path(Operation::Create, || path.clone())
+    .map(|file| NamedTempFile {
+        path: TempPath {
+            path: path.into_boxed_path(),
+        },
+        file,
+    })?;
+    if builder.sparse > 0 {
+        file.as_file()
+            .set_len(builder.sparse)
+            .with_err_path(Operation::Create, || file.path())?;
+    }
+    if builder.preallocate > 0 {
+        file.allocate(builder.preallocate)?;
+    }
+    Ok(file)
+}
+
+pub(crate) fn copy_into(
+    source: &File,
+    permissions: fs::Permissions,
+    file: &mut NamedTempFile,
+) -> io::Result<()> {
+    imp::copy(source, &file.file).with_err_path(Operation::Copy, || file.path())?;
+    file.file
+        .set_permissions(permissions)
+        .with_err_path(Operation::Copy, || file.path())?;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(())
+}
+
+pub(crate) fn copy_file(from: &Path, to: &Path) -> io::Result<()> {
+    copy_file_with(from, to, imp::copy)
+}
+
+pub(crate) fn reflink_file(from: &Path, to: &Path) -> io::Result<()> {
+    copy_file_with(from, to, imp::reflink)
+}
+
+fn copy_file_with(
+    from: &Path,
+    to: &Path,
+    copy: fn(&File, &File) -> io::Result<()>,
+) -> io::Result<()> {
+    let source = File::open(from).with_err_path(Operation::Copy, || from)?;
+    let permissions = source
+        .metadata()
+        .with_err_path(Operation::Copy, || from)?
+        .permissions();
+    let dest = imp::create_named(to, &mut OpenOptions::new(), None)
+        .with_err_path(Operation::Create, || to)?;
+    copy(&source, &dest).with_err_path(Operation::Copy, || to)?;
+    dest.set_permissions(permissions)
+        .with_err_path(Operation::Copy, || to)
+}
+
+pub(crate) fn create_anonymous(
+    dir: &Path,
+    builder: &Builder<'_, '_>,
+) -> io::Result<(File, TempFileStrategy)> {
+    let (file, strategy) = imp::create(dir, builder)?;
+    // The file has no name, so report errors against the directory.
+    if builder.sparse > 0 {
+        file.set_len(builder.sparse)
+            .with_err_path(Operation::Create, || dir)?;
+    }
+    if builder.preallocate > 0 {
+        imp::allocate(&file, builder.preallocate).with_err_path(Operation::Allocate, || dir)?;
+    }
+    Ok((file, strategy))
+}
+
+#[allow(clippy::io_other_error)]
+pub(crate) fn check_free_space(dir: &Path, required: u64) -> io::Result<()> {
+    let available = free_space(dir).with_err_path(Operation::Create, || dir)?;
+    if available < required {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!(
+                "not enough free space: {} bytes available, {} required",
+                available, required
+            ),
+        ))
+        .with_err_path(Operation::Create, || dir);
+    }
+    Ok(())
 }
@@ -0,0 +1,99 @@
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::path::PathBuf;
+
+use rustix::io::dup;
+
+/// A duplicate of a file descriptor that is inherited by child processes.
+///
+/// This makes it possible to share a file without a usable name, like one
+/// created with [`tempfile()`], with tools that only accept file paths: the
+/// child process can open the file through [`InheritableFile::path`], a
+/// `/dev/fd/N` path referring to its own copy of the descriptor.
+///
+/// The duplicate is closed when the `InheritableFile` is dropped, so keep it
+/// alive until the child process has been spawned. While it's alive, it is
+/// inherited by *every* process spawned by the current process, not just the
+/// intended one.
+///
+/// On Linux, opening `/dev/fd/N` reopens the file with an independent
+/// position. On other systems (e.g., macOS), it duplicates the descriptor, so
+/// the child shares the file position with the parent.
+///
+/// This type is only available on UNIX.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::io::{self, Write};
+/// use std::process::Command;
+/// use tempfile::{tempfile, InheritableFile};
+///
+/// # fn main() {
+/// #     if let Err(_) = run() {
+/// #         ::std::process::exit(1);
+/// #     }
+/// # }
+/// # fn run() -> Result<(), io::Error> {
+/// let mut file = tempfile()?;
+/// writeln!(file, "Brian was here. Briefly.")?;
+///
+/// let inheritable = InheritableFile::new(&file)?;
+/// let status = Command::new("cat")
+///     .arg(inheritable.to_command_arg())
+///     .status()?;
+/// assert!(status.success());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`tempfile()`]: fn.tempfile.html
+/// [`InheritableFile::path`]: #method.path
+pub struct InheritableFile {
+    file: File,
+}
+
+impl InheritableFile {
+    /// Duplicate `file`'s descriptor without the close-on-exec flag.
+    ///
+    /// # Errors
+    ///
+    /// If the descriptor can't be duplicated, `Err` is returned.
+    pub fn new(file: &File) -> io::Result<InheritableFile> {
+        // Unlike `F_DUPFD_CLOEXEC`, `dup` doesn't set close-on-exec on the
+        // new descriptor.
+        Ok(InheritableFile {
+            file: dup(file)?.into(),
+        })
+    }
+
+    /// Returns the `/dev/fd/N` path child processes can open the file with.
+    #[must_use]
+    pub fn path(&self) -> PathBuf {
+        PathBuf::from(format!("/dev/fd/{}", self.file.as_raw_fd()))
+    }
+
+    /// Returns the path as an argument for [`std::process::Command::arg`].
+    ///
+    /// [`std::process::Command::arg`]: https://doc.rust-lang.org/std/process/struct.Command.html#method.arg
+    #[must_use]
+    pub fn to_command_arg(&self) -> OsString {
+        self.path().into_os_string()
+    }
+}
+
+impl fmt::Debug for InheritableFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "InheritableFile({})", self.file.as_raw_fd())
+    }
+}
+
+impl AsRawFd for InheritableFile {
+    #[inline]
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
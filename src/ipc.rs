@@ -0,0 +1,204 @@
+use std::env;
+use std::io;
+use std::os::unix::net::{UnixDatagram, UnixListener};
+use std::path::{Path, PathBuf};
+
+use crate::error::{IoResultExt, Operation};
+use crate::{util, Builder, NamedTempFile, TempPath};
+
+/// Returns the size of `sockaddr_un::sun_path`, including the trailing NUL.
+fn sun_path_len() -> usize {
+    // SAFETY: `sockaddr_un` is a plain C struct, for which all zeroes is a
+    // valid value.
+    let addr: libc::sockaddr_un = unsafe { std::mem::zeroed() };
+    addr.sun_path.len()
+}
+
+/// A short, world-writable directory to fall back to when the default
+/// temporary directory is too long for a socket path.
+#[cfg(target_os = "android")]
+const SHORT_TMP_DIR: &str = "/data/local/tmp";
+#[cfg(not(target_os = "android"))]
+const SHORT_TMP_DIR: &str = "/tmp";
+
+impl<'a, 'b> Builder<'a, 'b> {
+    /// Create a temporary UNIX domain socket listener.
+    ///
+    /// The socket is bound to a randomized path inside of
+    /// [`std::env::temp_dir()`]. If that path would be too long for a socket
+    /// address (about 100 bytes, depending on the platform), `/tmp` is used
+    /// instead. The socket file is removed when the returned
+    /// `NamedTempFile` is dropped.
+    ///
+    /// This function is only available on UNIX.
+    ///
+    /// # Security
+    ///
+    /// This has the same [security implications][security] as
+    /// [`NamedTempFile`].
+    ///
+    /// # Errors
+    ///
+    /// If the socket can not be bound, `Err` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// use std::os::unix::net::UnixStream;
+    /// use tempfile::Builder;
+    ///
+    /// let listener = Builder::new().prefix("sock").unix_listener()?;
+    /// let stream = UnixStream::connect(listener.path())?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`std::env::temp_dir()`]: https://doc.rust-lang.org/std/env/fn.temp_dir.html
+    /// [security]: struct.NamedTempFile.html#security
+    /// [`NamedTempFile`]: struct.NamedTempFile.html
+    pub fn unix_listener(&self) -> io::Result<NamedTempFile<UnixListener>> {
+        self.unix_listener_in(self.socket_dir()?)
+    }
+
+    /// Create a temporary UNIX domain socket listener in the specified
+    /// directory.
+    ///
+    /// Unlike [`Builder::unix_listener`], there's no fallback if the path is
+    /// too long for a socket address.
+    ///
+    /// [`Builder::unix_listener`]: #method.unix_listener
+    pub fn unix_listener_in<P: AsRef<Path>>(
+        &self,
+        dir: P,
+    ) -> io::Result<NamedTempFile<UnixListener>> {
+        self.make_in(dir, |path| UnixListener::bind(path))
+    }
+
+    /// Create a temporary UNIX domain datagram socket.
+    ///
+    /// See [`Builder::unix_listener`] for details.
+    ///
+    /// [`Builder::unix_listener`]: #method.unix_listener
+    pub fn unix_datagram(&self) -> io::Result<NamedTempFile<UnixDatagram>> {
+        self.unix_datagram_in(self.socket_dir()?)
+    }
+
+    /// Create a temporary UNIX domain datagram socket in the specified
+    /// directory.
+    ///
+    /// See [`Builder::unix_listener_in`] for details.
+    ///
+    /// [`Builder::unix_listener_in`]: #method.unix_listener_in
+    pub fn unix_datagram_in<P: AsRef<Path>>(
+        &self,
+        dir: P,
+    ) -> io::Result<NamedTempFile<UnixDatagram>> {
+        self.make_in(dir, |path| UnixDatagram::bind(path))
+    }
+
+    /// Create a temporary named pipe (FIFO) inside of
+    /// [`std::env::temp_dir()`].
+    ///
+    /// The FIFO is created with mode `0o600` but not opened, as opening it
+    /// blocks until the other end is opened too. It is removed when the
+    /// returned `TempPath` is dropped.
+    ///
+    /// This function is only available on UNIX.
+    ///
+    /// # Errors
+    ///
+    /// If the FIFO can not be created, `Err` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io;
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// use tempfile::Builder;
+    ///
+    /// let fifo = Builder::new().fifo()?;
+    /// println!("{:?}", fifo);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`std::env::temp_dir()`]: https://doc.rust-lang.org/std/env/fn.temp_dir.html
+    pub fn fifo(&self) -> io::Result<TempPath> {
+        self.fifo_in(self.temp_dir()?)
+    }
+
+    /// Create a temporary named pipe (FIFO) in the specified directory.
+    ///
+    /// See [`Builder::fifo`] for details.
+    ///
+    /// [`Builder::fifo`]: #method.fifo
+    pub fn fifo_in<P: AsRef<Path>>(&self, dir: P) -> io::Result<TempPath> {
+        let storage;
+        let mut dir = dir.as_ref();
+        if !dir.is_absolute() {
+            storage = env::current_dir()?.join(dir);
+            dir = &storage;
+        }
+
+        util::create_helper(dir, self.prefix, self.suffix, self.random_len, |path| {
+            create_fifo(&path)
+                .with_err_path(Operation::Create, || &path)
+                .map(|_| TempPath::from_path(path))
+        })
+    }
+
+    /// Returns the directory to create sockets in by default.
+    fn socket_dir(&self) -> io::Result<PathBuf> {
+        let dir = self.temp_dir()?;
+        let len = dir.as_os_str().len()
+            + 1 // separator
+            + self.prefix.len()
+            + self.random_len
+            + self.suffix.len();
+        Ok(if len < sun_path_len() {
+            dir
+        } else {
+            PathBuf::from(SHORT_TMP_DIR)
+        })
+    }
+}
+
+#[cfg(not(any(target_os = "ios", target_os = "macos")))]
+fn create_fifo(path: &Path) -> io::Result<()> {
+    use rustix::fs::{cwd, mknodat, FileType, Mode};
+    Ok(mknodat(
+        cwd(),
+        path,
+        FileType::Fifo,
+        Mode::RUSR | Mode::WUSR,
+        0,
+    )?)
+}
+
+// rustix doesn't support `mknodat` on macOS and iOS.
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+fn create_fifo(path: &Path) -> io::Result<()> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    // SAFETY: `path` is a valid NUL-terminated string.
+    if unsafe { libc::mkfifo(path.as_ptr(), 0o600) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
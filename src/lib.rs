@@ -174,21 +174,44 @@ const NUM_RETRIES: u32 = 1 << 31;
 const NUM_RAND_CHARS: usize = 6;
 
 use std::ffi::OsStr;
-use std::fs::OpenOptions;
-use std::path::Path;
+use std::fs::{File, OpenOptions, Permissions};
+use std::path::{Path, PathBuf};
 use std::{env, io};
 
+use crate::error::IoResultExt;
+
 mod dir;
 mod error;
 mod file;
+#[cfg(unix)]
+mod inherit;
+#[cfg(unix)]
+mod ipc;
+mod limit;
+mod link;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+mod memfd;
+mod root;
 mod spooled;
+#[cfg(feature = "tokio")]
+pub mod tokio;
 mod util;
 
-pub use crate::dir::{tempdir, tempdir_in, TempDir};
+pub use crate::dir::{flush_background_cleanup, tempdir, tempdir_in, CopyMode, TempDir};
+pub use crate::error::{Error, Operation};
 pub use crate::file::{
-    tempfile, tempfile_in, NamedTempFile, PathPersistError, PersistError, TempPath,
+    tempfile, tempfile_in, tempfile_in_with_info, tempfile_with_info, NamedTempFile,
+    PathPersistError, PersistError, TempFileStrategy, TempPath,
+};
+#[cfg(unix)]
+pub use crate::inherit::InheritableFile;
+pub use crate::limit::{SizeLimitExceeded, SizeLimited};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use crate::memfd::{memfd, MemfdExt, MemfdOptions, Seals};
+pub use crate::root::TempRoot;
+pub use crate::spooled::{
+    spooled_tempfile, SpoolBudget, SpooledData, SpooledPersistError, SpooledTempFile,
 };
-pub use crate::spooled::{spooled_tempfile, SpooledTempFile};
 
 /// Create a new temporary file or directory with custom parameters.
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -197,6 +220,17 @@ pub struct Builder<'a, 'b> {
     prefix: &'a OsStr,
     suffix: &'b OsStr,
     append: bool,
+    background_drop: bool,
+    copy_mode: CopyMode,
+    strategy: Option<TempFileStrategy>,
+    permissions: Option<Permissions>,
+    #[cfg(unix)]
+    custom_flags: i32,
+    preallocate: u64,
+    sparse: u64,
+    free_space: u64,
+    root: Option<TempRoot>,
+    scratch_dir: Option<PathBuf>,
 }
 
 impl<'a, 'b> Default for Builder<'a, 'b> {
@@ -206,6 +240,17 @@ impl<'a, 'b> Default for Builder<'a, 'b> {
             prefix: OsStr::new(".tmp"),
             suffix: OsStr::new(""),
             append: false,
+            background_drop: false,
+            copy_mode: CopyMode::Copy,
+            strategy: None,
+            permissions: None,
+            #[cfg(unix)]
+            custom_flags: 0,
+            preallocate: 0,
+            sparse: 0,
+            free_space: 0,
+            root: None,
+            scratch_dir: None,
         }
     }
 }
@@ -391,6 +436,385 @@ impl<'a, 'b> Builder<'a, 'b> {
         self
     }
 
+    /// Set the permissions of new temporary files.
+    ///
+    /// On Unix, the mode is applied when creating the file, and is subject to
+    /// the process umask. On Windows, only the read-only flag is supported,
+    /// and it's only applied to named temporary files.
+    ///
+    /// Default: readable and writable by the owner only (`0o600` on Unix),
+    /// except for unnamed temporary files created with `O_TMPFILE` on Linux,
+    /// which keep the default mode of new files (`0o666`, less the umask).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// # #[cfg(unix)]
+    /// # {
+    /// use std::fs::Permissions;
+    /// use std::os::unix::fs::PermissionsExt;
+    /// use tempfile::Builder;
+    ///
+    /// let named_tempfile = Builder::new()
+    ///     .permissions(Permissions::from_mode(0o640))
+    ///     .tempfile()?;
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn permissions(&mut self, permissions: Permissions) -> &mut Self {
+        self.permissions = Some(permissions);
+        self
+    }
+
+    /// Pass custom flags to the `flags` argument of `open` when creating
+    /// temporary files, such as `O_DIRECT`, `O_SYNC` or `O_NOATIME`.
+    ///
+    /// The flags are added to the ones set by this crate, and can't remove
+    /// them: temporary files are always opened for reading and writing, and
+    /// named temporary files are always created exclusively
+    /// (`O_CREAT | O_EXCL`), so an existing file is never opened.
+    ///
+    /// This function is only available on Unix.
+    ///
+    /// Default: `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io;
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// # use tempfile::Builder;
+    /// # #[cfg(unix)]
+    /// # {
+    /// let named_tempfile = Builder::new()
+    ///     .custom_flags(libc::O_SYNC)
+    ///     .tempfile()?;
+    /// # }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(unix)]
+    pub fn custom_flags(&mut self, flags: i32) -> &mut Self {
+        self.custom_flags = flags;
+        self
+    }
+
+    /// Allocate disk space for the first `bytes` bytes of new temporary
+    /// files, right after creating them.
+    ///
+    /// If the space can't be allocated, for example because the disk is
+    /// full, creating the file fails. See [`NamedTempFile::allocate`] for
+    /// details.
+    ///
+    /// Default: `0` (don't preallocate).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io;
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// # use tempfile::Builder;
+    /// let named_tempfile = Builder::new()
+    ///     .preallocate(64 << 20)
+    ///     .tempfile()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`NamedTempFile::allocate`]: struct.NamedTempFile.html#method.allocate
+    pub fn preallocate(&mut self, bytes: u64) -> &mut Self {
+        self.preallocate = bytes;
+        self
+    }
+
+    /// Create new temporary files with a logical size of `len` bytes, without
+    /// allocating any disk space for them.
+    ///
+    /// The file reads back as zeros until written to, and only the regions
+    /// that are written to take up space, on filesystems that support sparse
+    /// files. See [`NamedTempFile::data_ranges`] to find those regions.
+    ///
+    /// Default: `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io;
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// # use tempfile::Builder;
+    /// let disk_image = Builder::new()
+    ///     .suffix(".img")
+    ///     .sparse(8 << 30)
+    ///     .tempfile()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`NamedTempFile::data_ranges`]: struct.NamedTempFile.html#method.data_ranges
+    pub fn sparse(&mut self, len: u64) -> &mut Self {
+        self.sparse = len;
+        self
+    }
+
+    /// Require at least `bytes` bytes of free space in the target directory's
+    /// filesystem before creating temporary files and directories.
+    ///
+    /// The free space available to the current user is checked with
+    /// `statvfs` (or `GetDiskFreeSpaceExW` on Windows) right before creating
+    /// the file or directory, so that running out of space is reported up
+    /// front instead of halfway through writing. Other processes may still use
+    /// up the space afterwards; use [`Builder::preallocate`] to reserve it.
+    ///
+    /// The check applies to [`tempfile`], [`tempdir`] and [`make`] and their
+    /// `_in` variants, and to [`anonymous_in`]. Symbolic links, FIFOs and sockets
+    /// don't hold any data, so they are created without checking.
+    ///
+    /// Default: `0` (don't check).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io;
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// # use tempfile::Builder;
+    /// let named_tempfile = Builder::new()
+    ///     .require_free_space(1 << 30)
+    ///     .tempfile()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Builder::preallocate`]: #method.preallocate
+    /// [`tempfile`]: #method.tempfile
+    /// [`anonymous_in`]: #method.anonymous_in
+    /// [`tempdir`]: #method.tempdir
+    /// [`make`]: #method.make
+    pub fn require_free_space(&mut self, bytes: u64) -> &mut Self {
+        self.free_space = bytes;
+        self
+    }
+
+    /// Returns the `OpenOptions` to create temporary files with, before the
+    /// flags that are always set by this crate.
+    fn open_options(&self) -> OpenOptions {
+        let mut open_options = OpenOptions::new();
+        open_options.append(self.append);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            open_options.custom_flags(self.custom_flags);
+        }
+        open_options
+    }
+
+    /// Returns the directory to create temporary files and directories in
+    /// when none is specified.
+    fn temp_dir(&self) -> io::Result<PathBuf> {
+        match self.root {
+            Some(ref root) => root.resolve(),
+            None => Ok(env::temp_dir()),
+        }
+    }
+
+    /// Set temporary directories to be removed on a background thread when
+    /// dropped.
+    ///
+    /// See [`TempDir::close_in_background`] for details. Only applies to
+    /// directories created with [`Builder::tempdir`] and
+    /// [`Builder::tempdir_in`].
+    ///
+    /// Default: `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// # use tempfile::Builder;
+    /// let tmp_dir = Builder::new()
+    ///     .background_drop(true)
+    ///     .tempdir()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn background_drop(&mut self, background_drop: bool) -> &mut Self {
+        self.background_drop = background_drop;
+        self
+    }
+
+    /// Set how [`Builder::tempdir_from`] populates the temporary directory
+    /// from its template.
+    ///
+    /// Default: [`CopyMode::Copy`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io;
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// # use tempfile::{Builder, CopyMode};
+    /// let tmp_dir = Builder::new()
+    ///     .copy_mode(CopyMode::Hardlink)
+    ///     .tempdir_from("tests/fixtures")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Builder::tempdir_from`]: #method.tempdir_from
+    /// [`CopyMode::Copy`]: enum.CopyMode.html#variant.Copy
+    pub fn copy_mode(&mut self, mode: CopyMode) -> &mut Self {
+        self.copy_mode = mode;
+        self
+    }
+
+    /// Require a specific strategy for creating unnamed temporary files.
+    ///
+    /// By default, unnamed temporary files are created with `O_TMPFILE` where
+    /// supported, falling back to creating a named file and immediately
+    /// unlinking it. Requiring [`TempFileStrategy::OTmpFile`] forbids that
+    /// fallback, so that the file is never visible in the directory, while
+    /// requiring [`TempFileStrategy::CreateUnlink`] skips `O_TMPFILE`
+    /// entirely. If the required strategy isn't supported, creating the file
+    /// fails.
+    ///
+    /// This only affects unnamed temporary files created by `Builder`, such
+    /// as with [`Builder::tempfile_unnamed`] or
+    /// [`Builder::anonymous_in_with_info`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io;
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// # use tempfile::{Builder, TempFileStrategy};
+    /// let (file, _) = Builder::new()
+    ///     .strategy(TempFileStrategy::OTmpFile)
+    ///     .anonymous_with_info()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`TempFileStrategy::OTmpFile`]: enum.TempFileStrategy.html#variant.OTmpFile
+    /// [`TempFileStrategy::CreateUnlink`]: enum.TempFileStrategy.html#variant.CreateUnlink
+    /// [`Builder::tempfile_unnamed`]: #method.tempfile_unnamed
+    /// [`Builder::anonymous_in_with_info`]: #method.anonymous_in_with_info
+    pub fn strategy(&mut self, strategy: TempFileStrategy) -> &mut Self {
+        self.strategy = Some(strategy);
+        self
+    }
+
+    /// Choose the directory to create temporary files and directories in
+    /// with a [`TempRoot`], instead of using [`std::env::temp_dir()`].
+    ///
+    /// This applies to every method that doesn't take a directory, such as
+    /// [`Builder::tempfile`] and [`Builder::tempdir`]. The directory is
+    /// resolved each time one of them is called.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io;
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// # use tempfile::{Builder, TempRoot};
+    /// let mut root = TempRoot::new();
+    /// root.system_candidates().not_tmpfs(true);
+    ///
+    /// let tmp_dir = Builder::new().root(&root).tempdir()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`TempRoot`]: struct.TempRoot.html
+    /// [`std::env::temp_dir()`]: https://doc.rust-lang.org/std/env/fn.temp_dir.html
+    /// [`Builder::tempfile`]: #method.tempfile
+    /// [`Builder::tempdir`]: #method.tempdir
+    pub fn root(&mut self, root: &TempRoot) -> &mut Self {
+        self.root = Some(root.clone());
+        self
+    }
+
+    /// Set a scratch directory for [`Builder::tempfile_for`] to create
+    /// temporary files in, instead of next to their destination.
+    ///
+    /// The scratch directory is only used for destinations on the same
+    /// filesystem (as reported by `st_dev` on UNIX, or the volume on
+    /// Windows), so that the temporary file can always be persisted. This
+    /// keeps half-written files out of the destination directory, for
+    /// example if it is watched by another process.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io;
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// # use tempfile::Builder;
+    /// let file = Builder::new()
+    ///     .scratch_dir("/srv/www/.scratch")
+    ///     .tempfile_for("/srv/www/index.html")?;
+    /// file.persist("/srv/www/index.html")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Builder::tempfile_for`]: #method.tempfile_for
+    pub fn scratch_dir<P: AsRef<Path>>(&mut self, dir: P) -> &mut Self {
+        self.scratch_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
     /// Create the named temporary file.
     ///
     /// # Security
@@ -424,7 +848,7 @@ impl<'a, 'b> Builder<'a, 'b> {
     /// [security]: struct.NamedTempFile.html#security
     /// [resource-leaking]: struct.NamedTempFile.html#resource-leaking
     pub fn tempfile(&self) -> io::Result<NamedTempFile> {
-        self.tempfile_in(&env::temp_dir())
+        self.tempfile_in(self.temp_dir()?)
     }
 
     /// Create the named temporary file in the specified directory.
@@ -460,15 +884,242 @@ impl<'a, 'b> Builder<'a, 'b> {
     /// [security]: struct.NamedTempFile.html#security
     /// [resource-leaking]: struct.NamedTempFile.html#resource-leaking
     pub fn tempfile_in<P: AsRef<Path>>(&self, dir: P) -> io::Result<NamedTempFile> {
+        if self.free_space > 0 {
+            file::check_free_space(dir.as_ref(), self.free_space)?;
+        }
         util::create_helper(
             dir.as_ref(),
             self.prefix,
             self.suffix,
             self.random_len,
-            |path| file::create_named(path, OpenOptions::new().append(self.append)),
+            |path| file::create_named(path, self),
         )
     }
 
+    /// Create the named temporary file on the same filesystem as `dest`, so
+    /// that it can be persisted to `dest` without failing with `EXDEV`
+    /// ("Invalid cross-device link").
+    ///
+    /// The file is created in the configured [scratch directory][scratch_dir]
+    /// if it is on the same filesystem as the parent directory of `dest`,
+    /// and in that parent directory otherwise. `dest` itself doesn't need to
+    /// exist, but its parent directory does.
+    ///
+    /// Note that on Linux, renaming between two mounts of the same
+    /// filesystem (such as bind mounts) fails even though they share
+    /// `st_dev`, so the scratch directory shouldn't be a separate mount
+    /// point.
+    ///
+    /// # Security
+    ///
+    /// See [the security][security] docs on `NamedTempFile`.
+    ///
+    /// # Resource leaking
+    ///
+    /// See [the resource leaking][resource-leaking] docs on `NamedTempFile`.
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be created, `Err` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io;
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// use std::io::Write;
+    /// use tempfile::Builder;
+    ///
+    /// let mut file = Builder::new().tempfile_for("config.toml")?;
+    /// writeln!(file, "answer = 42")?;
+    /// file.persist("config.toml")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [scratch_dir]: #method.scratch_dir
+    /// [security]: struct.NamedTempFile.html#security
+    /// [resource-leaking]: struct.NamedTempFile.html#resource-leaking
+    pub fn tempfile_for<P: AsRef<Path>>(&self, dest: P) -> io::Result<NamedTempFile> {
+        let parent = match dest.as_ref().parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let dir = match self.scratch_dir {
+            Some(ref scratch) if file::same_filesystem(scratch, parent).unwrap_or(false) => scratch,
+            _ => parent,
+        };
+        self.tempfile_in(dir)
+    }
+
+    /// Create an unnamed temporary file inside of `env::temp_dir()`.
+    ///
+    /// Unlike [`tempfile()`], this honors the options set on the `Builder`:
+    /// the file is opened in [append] mode and with the given [permissions],
+    /// and if it can't be created with `O_TMPFILE`, the [prefix], [suffix]
+    /// and [number of random bytes][rand_bytes] are used to name the file
+    /// that's created and immediately unlinked.
+    ///
+    /// # Security
+    ///
+    /// See [the security][security] docs on [`tempfile()`].
+    ///
+    /// # Resource leaking
+    ///
+    /// The temporary file will be automatically removed by the OS when the
+    /// last handle to it is closed.
+    ///
+    /// # Errors
+    ///
+    /// If the file can not be created, `Err` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// # use tempfile::Builder;
+    /// let file = Builder::new()
+    ///     .prefix("scratch")
+    ///     .append(true)
+    ///     .tempfile_unnamed()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`tempfile()`]: fn.tempfile.html
+    /// [security]: fn.tempfile.html#security
+    /// [append]: #method.append
+    /// [permissions]: #method.permissions
+    /// [prefix]: #method.prefix
+    /// [suffix]: #method.suffix
+    /// [rand_bytes]: #method.rand_bytes
+    pub fn tempfile_unnamed(&self) -> io::Result<File> {
+        self.anonymous_in(self.temp_dir()?)
+    }
+
+    /// Create an unnamed temporary file in the specified directory.
+    ///
+    /// See [`Builder::tempfile_unnamed`] for details.
+    ///
+    /// [`Builder::tempfile_unnamed`]: #method.tempfile_unnamed
+    pub fn anonymous_in<P: AsRef<Path>>(&self, dir: P) -> io::Result<File> {
+        self.anonymous_in_with_info(dir).map(|(file, _)| file)
+    }
+
+    /// Create an unnamed temporary file inside of `env::temp_dir()`, and
+    /// report how it was created.
+    ///
+    /// See [`Builder::anonymous_in_with_info`] for details.
+    ///
+    /// [`Builder::anonymous_in_with_info`]: #method.anonymous_in_with_info
+    pub fn anonymous_with_info(&self) -> io::Result<(File, TempFileStrategy)> {
+        self.anonymous_in_with_info(self.temp_dir()?)
+    }
+
+    /// Create an unnamed temporary file in the specified directory, and
+    /// report how it was created.
+    ///
+    /// Like [`tempfile_in()`], the file is automatically removed by the OS
+    /// when the last handle to it is closed. The strategy used to create the
+    /// file can be required with [`Builder::strategy`].
+    ///
+    /// # Errors
+    ///
+    /// If the file can not be created with the required strategy, `Err` is
+    /// returned.
+    ///
+    /// [`tempfile_in()`]: fn.tempfile_in.html
+    /// [`Builder::strategy`]: #method.strategy
+    pub fn anonymous_in_with_info<P: AsRef<Path>>(
+        &self,
+        dir: P,
+    ) -> io::Result<(File, TempFileStrategy)> {
+        if self.free_space > 0 {
+            file::check_free_space(dir.as_ref(), self.free_space)?;
+        }
+        file::create_anonymous(dir.as_ref(), self)
+    }
+
+    /// Create a named temporary file holding a copy of the file at `path`.
+    ///
+    /// The temporary file is created in the same directory as `path`, so that
+    /// after editing it, it can be atomically persisted back over the
+    /// original. On Linux, the copy is a reflink made with `FICLONE` on
+    /// filesystems that support it (such as Btrfs and XFS), sharing the
+    /// underlying extents instead of duplicating them, which makes copies of
+    /// large files cheap. Otherwise the contents are copied in the kernel
+    /// with `copy_file_range`, or read and written as a last resort. The
+    /// permissions of the original are applied to the copy, and the returned
+    /// file is positioned at the start.
+    ///
+    /// The [`sparse`] and [`preallocate`] options are ignored, so that the
+    /// copy has the same length as the original.
+    ///
+    /// # Security
+    ///
+    /// See [the security][security] docs on `NamedTempFile`.
+    ///
+    /// # Errors
+    ///
+    /// If `path` can not be read, or the copy can not be created, `Err` is
+    /// returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io;
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// use std::io::{Seek, SeekFrom, Write};
+    /// use tempfile::Builder;
+    ///
+    /// let mut copy = Builder::new().prefix(".config").copy_of("config.toml")?;
+    /// copy.seek(SeekFrom::End(0))?;
+    /// writeln!(copy, "verbose = true")?;
+    /// copy.persist("config.toml")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [security]: struct.NamedTempFile.html#security
+    /// [`sparse`]: #method.sparse
+    /// [`preallocate`]: #method.preallocate
+    pub fn copy_of<P: AsRef<Path>>(&self, path: P) -> io::Result<NamedTempFile> {
+        let path = path.as_ref();
+        let source = File::open(path).with_err_path(Operation::Copy, || path)?;
+        let permissions = source
+            .metadata()
+            .with_err_path(Operation::Copy, || path)?
+            .permissions();
+        let dir = match path.parent() {
+            Some(parent) if parent != Path::new("") => parent,
+            _ => Path::new("."),
+        };
+
+        // The copy must end up the same length as the original.
+        let mut builder = self.clone();
+        builder.sparse = 0;
+        builder.preallocate = 0;
+        let mut file = builder.tempfile_in(dir)?;
+        file::copy_into(&source, permissions, &mut file)?;
+        Ok(file)
+    }
+
     /// Attempts to make a temporary directory inside of `env::temp_dir()` whose
     /// name will have the prefix, `prefix`. The directory and
     /// everything inside it will be automatically deleted once the
@@ -498,7 +1149,7 @@ impl<'a, 'b> Builder<'a, 'b> {
     ///
     /// [resource-leaking]: struct.TempDir.html#resource-leaking
     pub fn tempdir(&self) -> io::Result<TempDir> {
-        self.tempdir_in(&env::temp_dir())
+        self.tempdir_in(self.temp_dir()?)
     }
 
     /// Attempts to make a temporary directory inside of `dir`.
@@ -537,7 +1188,55 @@ impl<'a, 'b> Builder<'a, 'b> {
             dir = &storage;
         }
 
-        util::create_helper(dir, self.prefix, self.suffix, self.random_len, dir::create)
+        if self.free_space > 0 {
+            file::check_free_space(dir, self.free_space)?;
+        }
+        util::create_helper(dir, self.prefix, self.suffix, self.random_len, |path| {
+            dir::create(path, self.background_drop)
+        })
+    }
+
+    /// Attempts to make a temporary directory inside of `env::temp_dir()`
+    /// holding a recursive copy of the directory `src`.
+    ///
+    /// Subdirectories, regular files and symbolic links are recreated, and
+    /// the permissions of files and subdirectories are preserved, except
+    /// that subdirectories are always left writable (and searchable) by their
+    /// owner, so that the temporary directory can be removed. Symbolic links
+    /// are copied as-is, not followed. Regular files are copied or linked
+    /// depending on the configured [`CopyMode`]. Other file types, such as
+    /// sockets and FIFOs, are rejected.
+    ///
+    /// The temporary directory itself keeps its default, private
+    /// permissions.
+    ///
+    /// # Resource leaking
+    ///
+    /// See [the resource leaking][resource-leaking] docs on `TempDir`.
+    ///
+    /// # Errors
+    ///
+    /// If the directory can not be created, or `src` can not be copied, `Err`
+    /// is returned and the partially populated directory is removed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tempfile::Builder;
+    ///
+    /// # use std::io;
+    /// # fn run() -> Result<(), io::Error> {
+    /// let tmp_dir = Builder::new().prefix("fixtures").tempdir_from("tests/fixtures")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`CopyMode`]: enum.CopyMode.html
+    /// [resource-leaking]: struct.TempDir.html#resource-leaking
+    pub fn tempdir_from<P: AsRef<Path>>(&self, src: P) -> io::Result<TempDir> {
+        let dir = self.tempdir()?;
+        dir::copy_tree(src.as_ref(), dir.path(), self.copy_mode)?;
+        Ok(dir)
     }
 
     /// Attempts to create a temporary file (or file-like object) using the
@@ -647,7 +1346,7 @@ impl<'a, 'b> Builder<'a, 'b> {
     where
         F: FnMut(&Path) -> io::Result<R>,
     {
-        self.make_in(&env::temp_dir(), f)
+        self.make_in(self.temp_dir()?, f)
     }
 
     /// This is the same as [`Builder::make`], except `dir` is used as the base
@@ -677,6 +1376,9 @@ impl<'a, 'b> Builder<'a, 'b> {
         F: FnMut(&Path) -> io::Result<R>,
         P: AsRef<Path>,
     {
+        if self.free_space > 0 {
+            file::check_free_space(dir.as_ref(), self.free_space)?;
+        }
         util::create_helper(
             dir.as_ref(),
             self.prefix,
@@ -0,0 +1,208 @@
+use std::error;
+use std::fmt;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// The error wrapped by the `io::Error` returned when a write to a
+/// [`SizeLimited`] writer would exceed its limit.
+///
+/// [`SizeLimited`]: struct.SizeLimited.html
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct SizeLimitExceeded {
+    limit: u64,
+}
+
+impl SizeLimitExceeded {
+    /// Returns the `SizeLimitExceeded` wrapped by `err`, if there is one.
+    pub fn from_io_error(err: &io::Error) -> Option<&SizeLimitExceeded> {
+        err.get_ref().and_then(|e| e.downcast_ref())
+    }
+
+    /// Returns the limit that would have been exceeded.
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+}
+
+impl fmt::Display for SizeLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "size limit of {} bytes exceeded", self.limit)
+    }
+}
+
+impl error::Error for SizeLimitExceeded {}
+
+/// A wrapper around a seekable writer, such as a [`NamedTempFile`] or a
+/// [`SpooledTempFile`], that refuses to grow it past a maximum length.
+///
+/// A write that would extend the length past the limit fails as a whole,
+/// without writing anything, with an `io::Error` wrapping a
+/// [`SizeLimitExceeded`]. Writes within the limit, including overwrites of
+/// existing data, are passed through.
+///
+/// The wrapper keeps track of the position and length of the inner writer,
+/// so they must not be changed behind its back. Likewise, growing the inner
+/// writer directly, for example with `set_len`, isn't limited.
+///
+/// Writers opened in append mode, such as temporary files created with
+/// [`Builder::append`], write to the end of the file whatever their position.
+/// Wrap them with [`SizeLimited::new_append`] instead of [`SizeLimited::new`],
+/// or their writes are checked against the wrong offset.
+///
+/// # Examples
+///
+/// ```
+/// # use std::io;
+/// # fn main() {
+/// #     if let Err(_) = run() {
+/// #         ::std::process::exit(1);
+/// #     }
+/// # }
+/// # fn run() -> Result<(), io::Error> {
+/// use std::io::Write;
+/// use tempfile::{SizeLimitExceeded, SizeLimited, SpooledTempFile};
+///
+/// let mut upload = SizeLimited::new(SpooledTempFile::new(4096), 10)?;
+/// upload.write_all(b"0123456789")?;
+///
+/// let err = upload.write_all(b"!").unwrap_err();
+/// assert_eq!(SizeLimitExceeded::from_io_error(&err).unwrap().limit(), 10);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`NamedTempFile`]: struct.NamedTempFile.html
+/// [`SpooledTempFile`]: struct.SpooledTempFile.html
+/// [`SizeLimitExceeded`]: struct.SizeLimitExceeded.html
+/// [`Builder::append`]: struct.Builder.html#method.append
+/// [`SizeLimited::new_append`]: #method.new_append
+/// [`SizeLimited::new`]: #method.new
+#[derive(Debug)]
+pub struct SizeLimited<W> {
+    inner: W,
+    limit: u64,
+    pos: u64,
+    len: u64,
+    append: bool,
+}
+
+impl<W: Seek> SizeLimited<W> {
+    /// Wrap `inner`, limiting its length to `limit` bytes.
+    ///
+    /// The position of `inner` is preserved.
+    ///
+    /// # Errors
+    ///
+    /// If the position or length of `inner` can't be determined, or the
+    /// length already exceeds `limit`, `Err` is returned.
+    pub fn new(inner: W, limit: u64) -> io::Result<SizeLimited<W>> {
+        SizeLimited::with_mode(inner, limit, false)
+    }
+
+    /// Wrap `inner`, which was opened in append mode, limiting its length to
+    /// `limit` bytes.
+    ///
+    /// Every write is checked as if it went to the end of `inner`, whatever
+    /// its position.
+    ///
+    /// # Errors
+    ///
+    /// If the position or length of `inner` can't be determined, or the
+    /// length already exceeds `limit`, `Err` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// use std::io::{Seek, SeekFrom, Write};
+    /// use tempfile::{Builder, SizeLimited};
+    ///
+    /// let mut log = SizeLimited::new_append(Builder::new().append(true).tempfile()?, 10)?;
+    /// log.write_all(b"0123456789")?;
+    ///
+    /// // The write would still go to the end of the file.
+    /// log.seek(SeekFrom::Start(0))?;
+    /// assert!(log.write_all(b"!").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new_append(inner: W, limit: u64) -> io::Result<SizeLimited<W>> {
+        SizeLimited::with_mode(inner, limit, true)
+    }
+
+    #[allow(clippy::seek_from_current)]
+    fn with_mode(mut inner: W, limit: u64, append: bool) -> io::Result<SizeLimited<W>> {
+        let pos = inner.seek(SeekFrom::Current(0))?;
+        let len = inner.seek(SeekFrom::End(0))?;
+        inner.seek(SeekFrom::Start(pos))?;
+        if len > limit {
+            return Err(exceeded(limit));
+        }
+        Ok(SizeLimited {
+            inner,
+            limit,
+            pos,
+            len,
+            append,
+        })
+    }
+}
+
+impl<W> SizeLimited<W> {
+    /// Returns the maximum length of the inner writer.
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// Returns a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Unwraps the inner writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+#[allow(clippy::io_other_error)]
+fn exceeded(limit: u64) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, SizeLimitExceeded { limit })
+}
+
+impl<W: Write> Write for SizeLimited<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let start = if self.append { self.len } else { self.pos };
+        if start.saturating_add(buf.len() as u64) > self.limit {
+            return Err(exceeded(self.limit));
+        }
+        let n = self.inner.write(buf)?;
+        self.pos = start + n as u64;
+        self.len = self.len.max(self.pos);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Read> Read for SizeLimited<W> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl<W: Seek> Seek for SizeLimited<W> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.pos = self.inner.seek(pos)?;
+        Ok(self.pos)
+    }
+}
@@ -0,0 +1,146 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::error::{IoResultExt, Operation};
+use crate::{util, Builder, TempPath};
+
+impl<'a, 'b> Builder<'a, 'b> {
+    /// Create a temporary symbolic link pointing at `target` inside of
+    /// [`std::env::temp_dir()`].
+    ///
+    /// The link (not its target) is removed when the returned `TempPath` is
+    /// dropped. See [`Builder::symlink_to_in`] for details.
+    ///
+    /// [`std::env::temp_dir()`]: https://doc.rust-lang.org/std/env/fn.temp_dir.html
+    /// [`Builder::symlink_to_in`]: #method.symlink_to_in
+    pub fn symlink_to<P: AsRef<Path>>(&self, target: P) -> io::Result<TempPath> {
+        self.symlink_to_in(self.temp_dir()?, target)
+    }
+
+    /// Create a temporary symbolic link pointing at `target` in the specified
+    /// directory.
+    ///
+    /// `target` is stored as given, so a relative target is resolved relative
+    /// to `dir`, just like with `ln -s`. The link (not its target) is removed
+    /// when the returned `TempPath` is dropped, and it can be atomically
+    /// renamed over an existing link with [`TempPath::persist`].
+    ///
+    /// On Windows, a directory symlink is created if `target` currently
+    /// refers to a directory, and a file symlink otherwise.
+    ///
+    /// # Errors
+    ///
+    /// If the link can not be created, `Err` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io;
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// use tempfile::Builder;
+    ///
+    /// // Atomically switch `/srv/app/current` to a new release.
+    /// let link = Builder::new()
+    ///     .prefix(".current")
+    ///     .symlink_to_in("/srv/app", "releases/v2")?;
+    /// link.persist("/srv/app/current")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`TempPath::persist`]: struct.TempPath.html#method.persist
+    pub fn symlink_to_in<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        dir: P,
+        target: Q,
+    ) -> io::Result<TempPath> {
+        let dir = absolute(dir.as_ref())?;
+        let target = target.as_ref();
+        util::create_helper(&dir, self.prefix, self.suffix, self.random_len, |path| {
+            symlink(target, &path)
+                .with_err_path(Operation::Create, || &path)
+                .map(|_| TempPath::from_path(path))
+        })
+    }
+
+    /// Create a temporary hard link to `source` next to it, in the same
+    /// directory.
+    ///
+    /// Hard links can't span filesystems, so unlike the other methods on
+    /// `Builder` this doesn't default to [`std::env::temp_dir()`]. See
+    /// [`Builder::hardlink_to_in`] for details.
+    ///
+    /// [`std::env::temp_dir()`]: https://doc.rust-lang.org/std/env/fn.temp_dir.html
+    /// [`Builder::hardlink_to_in`]: #method.hardlink_to_in
+    pub fn hardlink_to<P: AsRef<Path>>(&self, source: P) -> io::Result<TempPath> {
+        let source = absolute(source.as_ref())?;
+        let dir = source.parent().unwrap_or_else(|| Path::new("/"));
+        self.hardlink_to_in(dir, &source)
+    }
+
+    /// Create a temporary hard link to `source` in the specified directory.
+    ///
+    /// The link is removed when the returned `TempPath` is dropped, leaving
+    /// `source` untouched, and it can be atomically renamed over an existing
+    /// file with [`TempPath::persist`].
+    ///
+    /// # Errors
+    ///
+    /// If the link can not be created, for example because `dir` is on a
+    /// different filesystem than `source`, `Err` is returned.
+    ///
+    /// [`TempPath::persist`]: struct.TempPath.html#method.persist
+    pub fn hardlink_to_in<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        dir: P,
+        source: Q,
+    ) -> io::Result<TempPath> {
+        let dir = absolute(dir.as_ref())?;
+        let source = source.as_ref();
+        util::create_helper(&dir, self.prefix, self.suffix, self.random_len, |path| {
+            fs::hard_link(source, &path)
+                .with_err_path(Operation::Create, || &path)
+                .map(|_| TempPath::from_path(path))
+        })
+    }
+}
+
+fn absolute(path: &Path) -> io::Result<PathBuf> {
+    if path.is_absolute() {
+        Ok(path.to_path_buf())
+    } else {
+        Ok(env::current_dir()?.join(path))
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(unix)] {
+        pub(crate) fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+            std::os::unix::fs::symlink(target, link)
+        }
+    } else if #[cfg(windows)] {
+        pub(crate) fn symlink(target: &Path, link: &Path) -> io::Result<()> {
+            use std::os::windows::fs::{symlink_dir, symlink_file};
+            let resolved = link.parent().map_or_else(|| target.to_path_buf(), |p| p.join(target));
+            if resolved.is_dir() {
+                symlink_dir(target, link)
+            } else {
+                symlink_file(target, link)
+            }
+        }
+    } else {
+        pub(crate) fn symlink(_target: &Path, _link: &Path) -> io::Result<()> {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                "operation not supported on this platform",
+            ))
+        }
+    }
+}
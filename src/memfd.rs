@@ -0,0 +1,259 @@
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io;
+
+use rustix::fs::{fcntl_add_seals, fcntl_get_seals, memfd_create, MemfdFlags, SealFlags};
+
+/// Create a new anonymous, memory-backed temporary file.
+///
+/// The file is created with `memfd_create(2)` and lives entirely in memory
+/// (or swap). Unlike [`tempfile()`], it doesn't need a writable directory. The
+/// name is only used for debugging (it appears as `memfd:<name>` in
+/// `/proc/self/fd`) and doesn't need to be unique.
+///
+/// The file is created with the close-on-exec flag set. Use [`MemfdOptions`]
+/// for more configuration.
+///
+/// This function is only available on Linux and Android.
+///
+/// # Resource Leaking
+///
+/// The memory is released by the OS when the last handle to the file is
+/// closed.
+///
+/// # Errors
+///
+/// If the file can not be created, `Err` is returned.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{self, Write};
+///
+/// # fn main() {
+/// #     if let Err(_) = run() {
+/// #         ::std::process::exit(1);
+/// #     }
+/// # }
+/// # fn run() -> Result<(), io::Error> {
+/// let mut file = tempfile::memfd("scratch")?;
+/// writeln!(file, "Brian was here. Briefly.")?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`tempfile()`]: fn.tempfile.html
+/// [`MemfdOptions`]: struct.MemfdOptions.html
+pub fn memfd<S: AsRef<OsStr>>(name: S) -> io::Result<File> {
+    MemfdOptions::new().create(name)
+}
+
+/// Options for creating anonymous, memory-backed temporary files.
+///
+/// See [`memfd()`](fn.memfd.html).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MemfdOptions {
+    cloexec: bool,
+    allow_sealing: bool,
+    hugetlb: bool,
+}
+
+impl Default for MemfdOptions {
+    fn default() -> Self {
+        MemfdOptions {
+            cloexec: true,
+            allow_sealing: false,
+            hugetlb: false,
+        }
+    }
+}
+
+impl MemfdOptions {
+    /// Create a new set of options.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the close-on-exec flag (`MFD_CLOEXEC`) on the file.
+    ///
+    /// Default: `true`.
+    pub fn cloexec(&mut self, cloexec: bool) -> &mut Self {
+        self.cloexec = cloexec;
+        self
+    }
+
+    /// Allow seals to be added to the file (`MFD_ALLOW_SEALING`).
+    ///
+    /// See [`MemfdExt`](trait.MemfdExt.html).
+    ///
+    /// Default: `false`.
+    pub fn allow_sealing(&mut self, allow_sealing: bool) -> &mut Self {
+        self.allow_sealing = allow_sealing;
+        self
+    }
+
+    /// Back the file with huge pages (`MFD_HUGETLB`), using the system's
+    /// default huge page size.
+    ///
+    /// This requires huge pages to be configured on the system, and that
+    /// writes go through `mmap` rather than `write`.
+    ///
+    /// Default: `false`.
+    pub fn hugetlb(&mut self, hugetlb: bool) -> &mut Self {
+        self.hugetlb = hugetlb;
+        self
+    }
+
+    /// Create the file.
+    ///
+    /// # Errors
+    ///
+    /// If the file can not be created, `Err` is returned. In particular, the
+    /// name must not contain NUL bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io;
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// use tempfile::MemfdOptions;
+    ///
+    /// let file = MemfdOptions::new()
+    ///     .allow_sealing(true)
+    ///     .create("payload")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn create<S: AsRef<OsStr>>(&self, name: S) -> io::Result<File> {
+        let mut flags = MemfdFlags::empty();
+        if self.cloexec {
+            flags |= MemfdFlags::CLOEXEC;
+        }
+        if self.allow_sealing {
+            flags |= MemfdFlags::ALLOW_SEALING;
+        }
+        if self.hugetlb {
+            flags |= MemfdFlags::HUGETLB;
+        }
+        Ok(memfd_create(name.as_ref(), flags)?.into())
+    }
+}
+
+/// Sealing operations for memory-backed temporary files.
+///
+/// Seals restrict the operations allowed on a file, for every handle to it,
+/// and can't be removed once added. This makes it possible to hand a file to
+/// an untrusted process with the guarantee that it won't be modified.
+///
+/// Seals can only be added to files created with
+/// [`MemfdOptions::allow_sealing`]; adding seals to other files fails with
+/// `EPERM`.
+///
+/// This trait is only available on Linux and Android.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{self, Write};
+/// use tempfile::{MemfdExt, MemfdOptions};
+///
+/// # fn main() {
+/// #     if let Err(_) = run() {
+/// #         ::std::process::exit(1);
+/// #     }
+/// # }
+/// # fn run() -> Result<(), io::Error> {
+/// let mut file = MemfdOptions::new().allow_sealing(true).create("payload")?;
+/// file.write_all(b"Brian was here. Briefly.")?;
+///
+/// file.seal_shrink()?;
+/// file.seal_grow()?;
+/// file.seal_write()?;
+/// assert!(file.seals()?.write());
+/// assert!(file.write_all(b"Not anymore.").is_err());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`MemfdOptions::allow_sealing`]: struct.MemfdOptions.html#method.allow_sealing
+pub trait MemfdExt {
+    /// Prevent the contents of the file from being modified (`F_SEAL_WRITE`).
+    ///
+    /// # Errors
+    ///
+    /// This fails with `EBUSY` if the file is mapped writable and shared.
+    fn seal_write(&self) -> io::Result<()>;
+
+    /// Prevent the file from being shrunk (`F_SEAL_SHRINK`).
+    fn seal_shrink(&self) -> io::Result<()>;
+
+    /// Prevent the file from being grown (`F_SEAL_GROW`).
+    fn seal_grow(&self) -> io::Result<()>;
+
+    /// Prevent any further seals from being added (`F_SEAL_SEAL`).
+    fn seal_seal(&self) -> io::Result<()>;
+
+    /// Returns the seals currently set on the file.
+    fn seals(&self) -> io::Result<Seals>;
+}
+
+impl MemfdExt for File {
+    fn seal_write(&self) -> io::Result<()> {
+        Ok(fcntl_add_seals(self, SealFlags::WRITE)?)
+    }
+
+    fn seal_shrink(&self) -> io::Result<()> {
+        Ok(fcntl_add_seals(self, SealFlags::SHRINK)?)
+    }
+
+    fn seal_grow(&self) -> io::Result<()> {
+        Ok(fcntl_add_seals(self, SealFlags::GROW)?)
+    }
+
+    fn seal_seal(&self) -> io::Result<()> {
+        Ok(fcntl_add_seals(self, SealFlags::SEAL)?)
+    }
+
+    fn seals(&self) -> io::Result<Seals> {
+        Ok(Seals(fcntl_get_seals(self)?))
+    }
+}
+
+/// The set of seals on a memory-backed temporary file.
+///
+/// See [`MemfdExt::seals`](trait.MemfdExt.html#tymethod.seals).
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Seals(SealFlags);
+
+impl Seals {
+    /// Returns true if the contents can't be modified.
+    #[must_use]
+    pub fn write(self) -> bool {
+        self.0.contains(SealFlags::WRITE)
+    }
+
+    /// Returns true if the file can't be shrunk.
+    #[must_use]
+    pub fn shrink(self) -> bool {
+        self.0.contains(SealFlags::SHRINK)
+    }
+
+    /// Returns true if the file can't be grown.
+    #[must_use]
+    pub fn grow(self) -> bool {
+        self.0.contains(SealFlags::GROW)
+    }
+
+    /// Returns true if no further seals can be added. This is always the
+    /// case for files created without `allow_sealing`.
+    #[must_use]
+    pub fn seal(self) -> bool {
+        self.0.contains(SealFlags::SEAL)
+    }
+}
@@ -0,0 +1,189 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::error::{IoResultExt, Operation};
+use crate::file;
+
+/// Chooses the directory to create temporary files and directories in from a
+/// list of candidates.
+///
+/// [`std::env::temp_dir()`] is often a `tmpfs`, backed by (limited) memory,
+/// which is a poor fit for large temporary files. A `TempRoot` picks the
+/// first candidate directory that exists and satisfies its policy, or, with
+/// [`TempRoot::most_free_space`], the one with the most free space. Pass it
+/// to [`Builder::root`] to use it instead of `std::env::temp_dir()`.
+///
+/// The candidates are checked every time the directory is resolved, so
+/// changes to the filesystem (such as a full disk) are taken into account.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use std::io;
+/// # fn main() {
+/// #     if let Err(_) = run() {
+/// #         ::std::process::exit(1);
+/// #     }
+/// # }
+/// # fn run() -> Result<(), io::Error> {
+/// use tempfile::{Builder, TempRoot};
+///
+/// let mut root = TempRoot::new();
+/// root.system_candidates()
+///     .candidate("target/tmp")
+///     .not_tmpfs(true)
+///     .most_free_space(true);
+///
+/// let named_tempfile = Builder::new().root(&root).tempfile()?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`std::env::temp_dir()`]: https://doc.rust-lang.org/std/env/fn.temp_dir.html
+/// [`TempRoot::most_free_space`]: #method.most_free_space
+/// [`Builder::root`]: struct.Builder.html#method.root
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct TempRoot {
+    candidates: Vec<PathBuf>,
+    most_free_space: bool,
+    not_tmpfs: bool,
+    tmpfile: bool,
+    same_filesystem_as: Option<PathBuf>,
+}
+
+impl TempRoot {
+    /// Create a new `TempRoot` without any candidates.
+    pub fn new() -> TempRoot {
+        TempRoot::default()
+    }
+
+    /// Add a candidate directory.
+    ///
+    /// Candidates are considered in the order they were added.
+    pub fn candidate<P: AsRef<Path>>(&mut self, dir: P) -> &mut Self {
+        self.candidates.push(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Add the system's usual temporary directories as candidates.
+    ///
+    /// These are [`std::env::temp_dir()`] (which honors `TMPDIR` on UNIX)
+    /// and, on UNIX, `/var/tmp` and `XDG_RUNTIME_DIR` (if set).
+    ///
+    /// [`std::env::temp_dir()`]: https://doc.rust-lang.org/std/env/fn.temp_dir.html
+    pub fn system_candidates(&mut self) -> &mut Self {
+        self.candidates.push(env::temp_dir());
+        #[cfg(unix)]
+        {
+            self.candidates.push(PathBuf::from("/var/tmp"));
+            if let Some(dir) = env::var_os("XDG_RUNTIME_DIR") {
+                self.candidates.push(PathBuf::from(dir));
+            }
+        }
+        self
+    }
+
+    /// Pick the candidate with the most free space, instead of the first
+    /// one.
+    ///
+    /// Default: `false`.
+    pub fn most_free_space(&mut self, most_free_space: bool) -> &mut Self {
+        self.most_free_space = most_free_space;
+        self
+    }
+
+    /// Reject candidates on a `tmpfs`.
+    ///
+    /// The filesystem type is only known on Linux and Android; elsewhere no
+    /// directory is considered to be on a `tmpfs`.
+    ///
+    /// Default: `false`.
+    pub fn not_tmpfs(&mut self, not_tmpfs: bool) -> &mut Self {
+        self.not_tmpfs = not_tmpfs;
+        self
+    }
+
+    /// Reject candidates that don't support `O_TMPFILE`, so that unnamed
+    /// temporary files created in them are never visible in the directory.
+    ///
+    /// This is checked by creating (and immediately closing) an unnamed
+    /// temporary file. `O_TMPFILE` is only supported on Linux, so no
+    /// candidate is accepted elsewhere.
+    ///
+    /// Default: `false`.
+    pub fn require_tmpfile(&mut self, require_tmpfile: bool) -> &mut Self {
+        self.tmpfile = require_tmpfile;
+        self
+    }
+
+    /// Reject candidates that aren't on the same filesystem as `path`, so
+    /// that temporary files created in them can be persisted to (or next to)
+    /// `path`.
+    ///
+    /// `path` must exist when the directory is resolved.
+    pub fn same_filesystem_as<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+        self.same_filesystem_as = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Resolve the directory to create temporary files and directories in.
+    ///
+    /// Candidates that don't exist, aren't directories, or can't be checked
+    /// are skipped.
+    ///
+    /// # Errors
+    ///
+    /// If the path passed to [`TempRoot::same_filesystem_as`] can't be
+    /// accessed, or no candidate satisfies the policy, `Err` is returned.
+    ///
+    /// [`TempRoot::same_filesystem_as`]: #method.same_filesystem_as
+    pub fn resolve(&self) -> io::Result<PathBuf> {
+        if let Some(ref path) = self.same_filesystem_as {
+            fs::metadata(path).with_err_path(Operation::Metadata, || path)?;
+        }
+
+        let mut accepted = self.candidates.iter().filter(|dir| self.accepts(dir));
+        let found = if self.most_free_space {
+            // On a tie, prefer the earlier candidate.
+            let mut best: Option<(u64, &PathBuf)> = None;
+            for dir in accepted {
+                if let Ok(space) = file::free_space(dir) {
+                    match best {
+                        Some((best_space, _)) if best_space >= space => {}
+                        _ => best = Some((space, dir)),
+                    }
+                }
+            }
+            best.map(|(_, dir)| dir)
+        } else {
+            accepted.next()
+        };
+
+        found.cloned().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "no candidate temporary directory satisfies the policy",
+            )
+        })
+    }
+
+    fn accepts(&self, dir: &Path) -> bool {
+        if !dir.is_dir() {
+            return false;
+        }
+        if self.not_tmpfs && file::is_tmpfs(dir).unwrap_or(true) {
+            return false;
+        }
+        if self.tmpfile && !file::supports_tmpfile(dir) {
+            return false;
+        }
+        if let Some(ref path) = self.same_filesystem_as {
+            if !file::same_filesystem(dir, path).unwrap_or(false) {
+                return false;
+            }
+        }
+        true
+    }
+}
@@ -1,6 +1,14 @@
-use crate::file::tempfile;
+use crate::file::{tempfile, NamedTempFile, PathPersistError, TempPath};
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
 use std::fs::File;
-use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
+use std::io::{self, Cursor, IoSlice, IoSliceMut, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+type RollHook = Box<dyn FnMut(usize, &File) -> io::Result<()> + Send + Sync>;
 
 /// A wrapper for the two states of a `SpooledTempFile`.
 #[derive(Debug)]
@@ -9,14 +17,151 @@ pub enum SpooledData {
     OnDisk(File),
 }
 
+/// A memory budget shared by several `SpooledTempFile`s.
+///
+/// Each `SpooledTempFile` created with [`SpooledTempFile::with_budget`] draws
+/// the memory used by its in-memory buffer from the budget, in addition to
+/// respecting its own `max_size`. When the budget is exhausted, the spool
+/// requesting more memory is rolled over to disk instead, keeping the total
+/// amount of spooled data held in memory bounded.
+///
+/// Memory is returned to the budget when a spool rolls over to disk or is
+/// dropped.
+///
+/// The budget is approximate: it counts the length of the data held by each
+/// spool, not the capacity allocated for it, which may be larger. It also
+/// only covers data while it is in a spool. Once the contents are taken out
+/// with [`into_inner`], [`into_bytes`] or [`into_reader`], their memory is
+/// returned to the budget even though the caller may still hold on to it.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{self, Write};
+/// use std::sync::Arc;
+/// use tempfile::{SpoolBudget, SpooledTempFile};
+///
+/// # fn main() {
+/// #     if let Err(_) = run() {
+/// #         ::std::process::exit(1);
+/// #     }
+/// # }
+/// # fn run() -> Result<(), io::Error> {
+/// let budget = Arc::new(SpoolBudget::new(20));
+/// let mut first = SpooledTempFile::with_budget(15, budget.clone());
+/// let mut second = SpooledTempFile::with_budget(15, budget.clone());
+///
+/// first.write_all(b"0123456789")?;
+/// assert_eq!(budget.used(), 10);
+///
+/// // There's only room for another 10 bytes in the budget, so this spool
+/// // rolls over to disk even though it's below its own `max_size`.
+/// second.write_all(b"abcdefghijkl")?;
+/// assert!(second.is_rolled());
+/// assert_eq!(budget.used(), 10);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`SpooledTempFile::with_budget`]: struct.SpooledTempFile.html#method.with_budget
+/// [`into_inner`]: struct.SpooledTempFile.html#method.into_inner
+/// [`into_bytes`]: struct.SpooledTempFile.html#method.into_bytes
+/// [`into_reader`]: struct.SpooledTempFile.html#method.into_reader
+#[derive(Debug)]
+pub struct SpoolBudget {
+    limit: usize,
+    used: AtomicUsize,
+}
+
+impl SpoolBudget {
+    /// Create a new budget allowing up to `limit` bytes to be held in memory.
+    #[must_use]
+    pub fn new(limit: usize) -> SpoolBudget {
+        SpoolBudget {
+            limit,
+            used: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the total number of bytes this budget allows.
+    #[must_use]
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Returns the number of bytes currently held in memory by spools drawing
+    /// from this budget.
+    #[must_use]
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::SeqCst)
+    }
+
+    fn try_acquire(&self, size: usize) -> bool {
+        let limit = self.limit;
+        self.used
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |used| {
+                used.checked_add(size).filter(|&total| total <= limit)
+            })
+            .is_ok()
+    }
+
+    fn release(&self, size: usize) {
+        self.used.fetch_sub(size, Ordering::SeqCst);
+    }
+}
+
+/// The share of a `SpoolBudget` currently held by a single spool.
+#[derive(Debug)]
+struct Reservation {
+    budget: Arc<SpoolBudget>,
+    size: usize,
+}
+
+impl Reservation {
+    /// Grows or shrinks the reservation to `size` bytes. Returns false if the
+    /// budget can't accommodate it.
+    fn resize(&mut self, size: usize) -> bool {
+        if size > self.size {
+            if !self.budget.try_acquire(size - self.size) {
+                return false;
+            }
+        } else {
+            self.budget.release(self.size - size);
+        }
+        self.size = size;
+        true
+    }
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        self.budget.release(self.size);
+    }
+}
+
 /// An object that behaves like a regular temporary file, but keeps data in
 /// memory until it reaches a configured size, at which point the data is
 /// written to a temporary file on disk, and further operations use the file
 /// on disk.
-#[derive(Debug)]
 pub struct SpooledTempFile {
     max_size: usize,
     inner: SpooledData,
+    on_roll: Option<RollHook>,
+    reservation: Option<Reservation>,
+    spill_dir: Option<PathBuf>,
+    spill_path: Option<TempPath>,
+}
+
+impl fmt::Debug for SpooledTempFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpooledTempFile")
+            .field("max_size", &self.max_size)
+            .field("inner", &self.inner)
+            .field("budget", &self.reservation.as_ref().map(|r| &r.budget))
+            .field("spill_dir", &self.spill_dir)
+            .field("spill_path", &self.spill_path)
+            .finish()
+    }
 }
 
 /// Create a new spooled temporary file.
@@ -69,9 +214,101 @@ impl SpooledTempFile {
         SpooledTempFile {
             max_size,
             inner: SpooledData::InMemory(Cursor::new(Vec::new())),
+            on_roll: None,
+            reservation: None,
+            spill_dir: None,
+            spill_path: None,
+        }
+    }
+
+    /// Create a new spooled temporary file drawing its memory from a shared
+    /// [`SpoolBudget`].
+    ///
+    /// The file rolls over to disk once its size exceeds `max_size`, or as
+    /// soon as `budget` can't accommodate the data it holds in memory,
+    /// whichever comes first.
+    ///
+    /// [`SpoolBudget`]: struct.SpoolBudget.html
+    #[must_use]
+    pub fn with_budget(max_size: usize, budget: Arc<SpoolBudget>) -> SpooledTempFile {
+        SpooledTempFile {
+            reservation: Some(Reservation { budget, size: 0 }),
+            ..SpooledTempFile::new(max_size)
         }
     }
 
+    /// Roll over to a named temporary file in `dir` instead of an unnamed one.
+    ///
+    /// This makes [`SpooledTempFile::persist`] a simple rename once the data
+    /// is on disk, as long as `dir` is on the same filesystem as the
+    /// destination. The named file is deleted when the `SpooledTempFile` is
+    /// dropped, like a [`NamedTempFile`].
+    ///
+    /// This has no effect if the file has already been rolled over.
+    ///
+    /// # Security
+    ///
+    /// Once rolled over, the data is reachable through a path, with the same
+    /// [security implications][security] as `NamedTempFile`.
+    ///
+    /// [`SpooledTempFile::persist`]: #method.persist
+    /// [`NamedTempFile`]: struct.NamedTempFile.html
+    /// [security]: struct.NamedTempFile.html#security
+    pub fn spill_named_in<P: AsRef<Path>>(&mut self, dir: P) {
+        self.spill_dir = Some(dir.as_ref().to_owned());
+    }
+
+    /// Returns the path of the temporary file the data has been rolled over
+    /// to, if it has been rolled over to a named file.
+    ///
+    /// See [`SpooledTempFile::spill_named_in`].
+    ///
+    /// [`SpooledTempFile::spill_named_in`]: #method.spill_named_in
+    #[must_use]
+    pub fn path(&self) -> Option<&Path> {
+        self.spill_path.as_deref()
+    }
+
+    /// Registers a hook to be called when the data is rolled over to disk.
+    ///
+    /// The hook is passed the size of the in-memory buffer at the time of the
+    /// spill and the temporary file the buffer has just been written to. If
+    /// the hook returns an error, the roll over is aborted, the data stays in
+    /// memory, and the error is returned from the operation that triggered the
+    /// roll over (e.g., `write`, `set_len`, or `roll`).
+    ///
+    /// Any previously registered hook is replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tempfile::SpooledTempFile;
+    /// use std::io::{self, Write};
+    ///
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// let mut file = SpooledTempFile::new(15);
+    /// file.on_roll(|_size, _file| {
+    ///     Err(io::Error::new(io::ErrorKind::PermissionDenied, "spilling to disk is forbidden"))
+    /// });
+    ///
+    /// writeln!(file, "short line")?;
+    /// assert!(writeln!(file, "marvin gardens").is_err());
+    /// assert!(!file.is_rolled());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_roll<F>(&mut self, hook: F)
+    where
+        F: FnMut(usize, &File) -> io::Result<()> + Send + Sync + 'static,
+    {
+        self.on_roll = Some(Box::new(hook));
+    }
+
     /// Returns true if the file has been rolled over to disk.
     #[must_use]
     pub fn is_rolled(&self) -> bool {
@@ -85,18 +322,31 @@ impl SpooledTempFile {
     /// if already rolled over.
     pub fn roll(&mut self) -> io::Result<()> {
         if !self.is_rolled() {
-            let mut file = tempfile()?;
+            let (mut file, path) = match self.spill_dir {
+                Some(ref dir) => {
+                    let (file, path) = NamedTempFile::new_in(dir)?.into_parts();
+                    (file, Some(path))
+                }
+                None => (tempfile()?, None),
+            };
             if let SpooledData::InMemory(ref mut cursor) = self.inner {
                 file.write_all(cursor.get_ref())?;
                 file.seek(SeekFrom::Start(cursor.position()))?;
+                if let Some(ref mut hook) = self.on_roll {
+                    hook(cursor.get_ref().len(), &file)?;
+                }
             }
             self.inner = SpooledData::OnDisk(file);
+            self.spill_path = path;
+            if let Some(ref mut reservation) = self.reservation {
+                reservation.resize(0);
+            }
         }
         Ok(())
     }
 
     pub fn set_len(&mut self, size: u64) -> Result<(), io::Error> {
-        if size as usize > self.max_size {
+        if size as usize > self.max_size || !self.reserve(size as usize) {
             self.roll()?; // does nothing if already rolled over
         }
         match self.inner {
@@ -108,31 +358,326 @@ impl SpooledTempFile {
         }
     }
 
+    /// Accounts for `size` bytes of in-memory data against the budget, if any.
+    /// Returns false if the budget is exhausted.
+    fn reserve(&mut self, size: usize) -> bool {
+        match (&self.inner, &mut self.reservation) {
+            (SpooledData::InMemory(_), Some(reservation)) => reservation.resize(size),
+            _ => true,
+        }
+    }
+
+    /// Rolls over to disk if writing `len` bytes at `offset` would exceed
+    /// `max_size` or the budget, or couldn't be addressed in memory at all.
+    fn prepare_write(&mut self, offset: u64, len: usize) -> io::Result<()> {
+        let mut rolling = false;
+        if let SpooledData::InMemory(ref mut cursor) = self.inner {
+            let end = usize::try_from(offset)
+                .ok()
+                .and_then(|offset| offset.checked_add(len));
+            rolling = match end {
+                Some(end) => {
+                    let len = end.max(cursor.get_ref().len());
+                    end > self.max_size || !self.reserve(len)
+                }
+                None => true,
+            };
+        }
+        if rolling {
+            self.roll()?;
+        }
+        Ok(())
+    }
+
+    /// Reads a number of bytes starting from a given offset.
+    ///
+    /// Returns the number of bytes read. Unlike `read`, this doesn't use or
+    /// move the current position, in memory or on disk.
+    ///
+    /// # Errors
+    ///
+    /// Positional reads from the file on disk aren't supported on all
+    /// platforms. On Windows, the position is saved and restored around the
+    /// read, so `read_at` shouldn't be used concurrently with other I/O on the
+    /// same file.
+    pub fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        match self.inner {
+            SpooledData::InMemory(ref cursor) => {
+                let data = cursor.get_ref();
+                if offset >= data.len() as u64 {
+                    return Ok(0);
+                }
+                let data = &data[offset as usize..];
+                let n = data.len().min(buf.len());
+                buf[..n].copy_from_slice(&data[..n]);
+                Ok(n)
+            }
+            SpooledData::OnDisk(ref file) => file_read_at(file, buf, offset),
+        }
+    }
+
+    /// Writes a number of bytes starting from a given offset.
+    ///
+    /// Returns the number of bytes written. Unlike `write`, this doesn't use
+    /// or move the current position, in memory or on disk. Writing past the
+    /// end of the data fills the gap with zeros, and rolls over to disk if the
+    /// resulting size exceeds `max_size`.
+    ///
+    /// # Errors
+    ///
+    /// Positional writes to the file on disk aren't supported on all
+    /// platforms. On Windows, the position is saved and restored around the
+    /// write, so `write_at` shouldn't be used concurrently with other I/O on
+    /// the same file.
+    pub fn write_at(&mut self, buf: &[u8], offset: u64) -> io::Result<usize> {
+        self.prepare_write(offset, buf.len())?;
+        match self.inner {
+            SpooledData::InMemory(ref mut cursor) => {
+                let data = cursor.get_mut();
+                let start = offset as usize;
+                let end = start + buf.len();
+                if data.len() < end {
+                    data.resize(end, 0);
+                }
+                data[start..end].copy_from_slice(buf);
+                Ok(buf.len())
+            }
+            SpooledData::OnDisk(ref file) => file_write_at(file, buf, offset),
+        }
+    }
+
+    /// Persist the data at the target path.
+    ///
+    /// If the data has been rolled over to a named temporary file (see
+    /// [`SpooledTempFile::spill_named_in`]), that file is renamed to the target
+    /// path. Otherwise, the data is first written to a new temporary file
+    /// next to the target path, which is then renamed. Either way, if a file
+    /// exists at the target path, it is atomically replaced.
+    ///
+    /// On success, the persisted file is returned, positioned at the current
+    /// position. If this method fails, it will return `self` in the resulting
+    /// [`SpooledPersistError`].
+    ///
+    /// Note: Named temporary files cannot be persisted across filesystems,
+    /// so the directory passed to `spill_named_in` should be on the same
+    /// filesystem as the target path.
+    ///
+    /// # Errors
+    ///
+    /// If the data cannot be written or moved to the new location, `Err` is
+    /// returned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io::{self, Write};
+    /// use tempfile::SpooledTempFile;
+    ///
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// let mut file = SpooledTempFile::new(1024);
+    /// file.spill_named_in(".");
+    /// writeln!(file, "Brian was here. Briefly.")?;
+    ///
+    /// file.persist("./saved_file.txt")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`SpooledTempFile::spill_named_in`]: #method.spill_named_in
+    /// [`SpooledPersistError`]: struct.SpooledPersistError.html
+    #[allow(clippy::result_large_err)]
+    pub fn persist<P: AsRef<Path>>(mut self, new_path: P) -> Result<File, SpooledPersistError> {
+        let new_path = new_path.as_ref();
+        if let Some(path) = self.spill_path.take() {
+            return match path.persist(new_path) {
+                Ok(()) => match self.inner {
+                    SpooledData::OnDisk(file) => Ok(file),
+                    SpooledData::InMemory(_) => unreachable!("named spill file without data"),
+                },
+                Err(PathPersistError { error, path, .. }) => {
+                    self.spill_path = Some(path);
+                    Err(SpooledPersistError {
+                        error,
+                        file: self,
+                        destination: new_path.to_path_buf(),
+                    })
+                }
+            };
+        }
+        match self.persist_copy(new_path) {
+            Ok(file) => Ok(file),
+            Err(error) => Err(SpooledPersistError {
+                error,
+                file: self,
+                destination: new_path.to_path_buf(),
+            }),
+        }
+    }
+
+    /// Copies the data to a new temporary file next to `new_path`, and
+    /// persists it there.
+    #[allow(clippy::seek_from_current)]
+    fn persist_copy(&mut self, new_path: &Path) -> io::Result<File> {
+        let dir = match new_path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let mut tmp = NamedTempFile::new_in(dir)?;
+        match self.inner {
+            SpooledData::InMemory(ref cursor) => {
+                tmp.write_all(cursor.get_ref())?;
+                tmp.seek(SeekFrom::Start(cursor.position()))?;
+            }
+            SpooledData::OnDisk(ref mut file) => {
+                let pos = file.seek(SeekFrom::Current(0))?;
+                file.seek(SeekFrom::Start(0))?;
+                let copied = io::copy(file, &mut tmp);
+                file.seek(SeekFrom::Start(pos))?;
+                copied?;
+                tmp.seek(SeekFrom::Start(pos))?;
+            }
+        }
+        Ok(tmp.persist(new_path)?)
+    }
+
     /// Consumes and returns the inner `SpooledData` type.
+    ///
+    /// If the data has been rolled over to a named file, the file is deleted.
     #[must_use]
     pub fn into_inner(self) -> SpooledData {
         self.inner
     }
+
+    /// Consumes the spooled file and returns its entire contents, reading
+    /// them back from disk if the file has been rolled over.
+    ///
+    /// # Errors
+    ///
+    /// If the contents can't be read back from disk, `Err` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tempfile::spooled_tempfile;
+    /// use std::io::{self, Write};
+    ///
+    /// # fn main() {
+    /// #     if let Err(_) = run() {
+    /// #         ::std::process::exit(1);
+    /// #     }
+    /// # }
+    /// # fn run() -> Result<(), io::Error> {
+    /// let mut file = spooled_tempfile(15);
+    /// writeln!(file, "short line")?;
+    /// assert_eq!(file.into_bytes()?, b"short line\n");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_bytes(self) -> io::Result<Vec<u8>> {
+        match self.inner {
+            SpooledData::InMemory(cursor) => Ok(cursor.into_inner()),
+            SpooledData::OnDisk(mut file) => {
+                let mut buf = Vec::new();
+                file.seek(SeekFrom::Start(0))?;
+                file.read_to_end(&mut buf)?;
+                Ok(buf)
+            }
+        }
+    }
+
+    /// Consumes the spooled file and returns the inner `SpooledData`,
+    /// rewound to the start so it can be read from.
+    ///
+    /// # Errors
+    ///
+    /// If the file on disk can't be rewound, `Err` is returned.
+    pub fn into_reader(self) -> io::Result<SpooledData> {
+        let mut inner = self.inner;
+        inner.seek(SeekFrom::Start(0))?;
+        Ok(inner)
+    }
+
+    /// Rolls over to disk if necessary, and returns the underlying file.
+    ///
+    /// The file's position is left at the current position of the spooled
+    /// file. If the data has been rolled over to a named file, the file is
+    /// deleted.
+    ///
+    /// # Errors
+    ///
+    /// If the data can't be rolled over to disk, `Err` is returned.
+    pub fn into_file(mut self) -> io::Result<File> {
+        self.roll()?;
+        match self.inner {
+            SpooledData::OnDisk(file) => Ok(file),
+            SpooledData::InMemory(_) => unreachable!("spooled file not rolled over"),
+        }
+    }
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(unix)] {
+        fn file_read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+            std::os::unix::fs::FileExt::read_at(file, buf, offset)
+        }
+
+        fn file_write_at(file: &File, buf: &[u8], offset: u64) -> io::Result<usize> {
+            std::os::unix::fs::FileExt::write_at(file, buf, offset)
+        }
+    } else if #[cfg(windows)] {
+        // `seek_read` and `seek_write` move the file position, so put it back.
+        #[allow(clippy::seek_from_current)]
+        fn file_read_at(mut file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+            let pos = file.seek(SeekFrom::Current(0))?;
+            let result = std::os::windows::fs::FileExt::seek_read(file, buf, offset);
+            file.seek(SeekFrom::Start(pos))?;
+            result
+        }
+
+        #[allow(clippy::seek_from_current)]
+        fn file_write_at(mut file: &File, buf: &[u8], offset: u64) -> io::Result<usize> {
+            let pos = file.seek(SeekFrom::Current(0))?;
+            let result = std::os::windows::fs::FileExt::seek_write(file, buf, offset);
+            file.seek(SeekFrom::Start(pos))?;
+            result
+        }
+    } else {
+        fn file_read_at(_file: &File, _buf: &mut [u8], _offset: u64) -> io::Result<usize> {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                "operation not supported on this platform",
+            ))
+        }
+
+        fn file_write_at(_file: &File, _buf: &[u8], _offset: u64) -> io::Result<usize> {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                "operation not supported on this platform",
+            ))
+        }
+    }
 }
 
 impl Read for SpooledTempFile {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        match self.inner {
-            SpooledData::InMemory(ref mut cursor) => cursor.read(buf),
-            SpooledData::OnDisk(ref mut file) => file.read(buf),
-        }
+        self.inner.read(buf)
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        self.inner.read_vectored(bufs)
     }
 }
 
 impl Write for SpooledTempFile {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         // roll over to file if necessary
-        let mut rolling = false;
-        if let SpooledData::InMemory(ref mut cursor) = self.inner {
-            rolling = cursor.position() as usize + buf.len() > self.max_size;
-        }
-        if rolling {
-            self.roll()?;
+        if let SpooledData::InMemory(ref cursor) = self.inner {
+            self.prepare_write(cursor.position(), buf.len())?;
         }
 
         // write the bytes
@@ -142,6 +687,20 @@ impl Write for SpooledTempFile {
         }
     }
 
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        // roll over to file if necessary
+        if let SpooledData::InMemory(ref cursor) = self.inner {
+            let len = bufs.iter().map(|b| b.len()).sum();
+            self.prepare_write(cursor.position(), len)?;
+        }
+
+        // write the bytes
+        match self.inner {
+            SpooledData::InMemory(ref mut cursor) => cursor.write_vectored(bufs),
+            SpooledData::OnDisk(ref mut file) => file.write_vectored(bufs),
+        }
+    }
+
     #[inline]
     fn flush(&mut self) -> io::Result<()> {
         match self.inner {
@@ -151,11 +710,77 @@ impl Write for SpooledTempFile {
     }
 }
 
-impl Seek for SpooledTempFile {
+/// Error returned when persisting a spooled temporary file fails.
+#[derive(Debug)]
+pub struct SpooledPersistError {
+    /// The underlying IO error.
+    pub error: io::Error,
+    /// The spooled temporary file that couldn't be persisted.
+    pub file: SpooledTempFile,
+    /// The path the spooled temporary file was being persisted to.
+    pub destination: PathBuf,
+}
+
+impl From<SpooledPersistError> for io::Error {
+    #[inline]
+    fn from(error: SpooledPersistError) -> io::Error {
+        match error.file.spill_path {
+            Some(ref path) => crate::error::persist_error(error.error, path, &error.destination),
+            None => error.error,
+        }
+    }
+}
+
+impl From<SpooledPersistError> for SpooledTempFile {
+    #[inline]
+    fn from(error: SpooledPersistError) -> SpooledTempFile {
+        error.file
+    }
+}
+
+impl fmt::Display for SpooledPersistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to persist spooled temporary file to {:?}: {}",
+            self.destination, self.error
+        )
+    }
+}
+
+impl error::Error for SpooledPersistError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl Read for SpooledData {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match *self {
+            SpooledData::InMemory(ref mut cursor) => cursor.read(buf),
+            SpooledData::OnDisk(ref mut file) => file.read(buf),
+        }
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize> {
+        match *self {
+            SpooledData::InMemory(ref mut cursor) => cursor.read_vectored(bufs),
+            SpooledData::OnDisk(ref mut file) => file.read_vectored(bufs),
+        }
+    }
+}
+
+impl Seek for SpooledData {
     fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
-        match self.inner {
+        match *self {
             SpooledData::InMemory(ref mut cursor) => cursor.seek(pos),
             SpooledData::OnDisk(ref mut file) => file.seek(pos),
         }
     }
 }
+
+impl Seek for SpooledTempFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
@@ -0,0 +1,407 @@
+//! Async temporary files and directories for use with [tokio].
+//!
+//! Creating, persisting and deleting temporary files and directories involves
+//! blocking filesystem calls. The types in this module wrap
+//! [`tokio::fs::File`] and run those calls on tokio's blocking thread pool so
+//! they don't stall the executor.
+//!
+//! These functions must be called from within a tokio runtime.
+//!
+//! This module is only available with the `tokio` feature, which raises the
+//! minimum required Rust version to 1.64 (or to that of the tokio release in
+//! use, if higher).
+//!
+//! # Examples
+//!
+//! ```
+//! # fn main() -> std::io::Result<()> {
+//! # tokio::runtime::Builder::new_current_thread().build()?.block_on(async {
+//! use tokio::io::AsyncWriteExt;
+//!
+//! let mut file = tempfile::tokio::NamedTempFile::new().await?;
+//! file.write_all(b"Brian was here. Briefly.").await?;
+//! file.close().await?;
+//! # Ok(())
+//! # })
+//! # }
+//! ```
+//!
+//! [tokio]: https://docs.rs/tokio
+//! [`tokio::fs::File`]: https://docs.rs/tokio/1/tokio/fs/struct.File.html
+
+use std::error;
+use std::fmt;
+use std::io::{self, SeekFrom};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use ::tokio::fs::File;
+use ::tokio::io::{AsyncRead, AsyncSeek, AsyncWrite, ReadBuf};
+use ::tokio::runtime::Handle;
+
+use crate::{PathPersistError, TempPath};
+
+mod spooled;
+
+pub use self::spooled::{spooled_tempfile, SpooledTempFile};
+
+/// Runs `f` on the blocking thread pool, propagating panics.
+async fn unblock<F, T>(f: F) -> T
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    match ::tokio::task::spawn_blocking(f).await {
+        Ok(res) => res,
+        Err(e) => match e.try_into_panic() {
+            Ok(payload) => panic::resume_unwind(payload),
+            Err(e) => panic!("blocking task failed: {}", e),
+        },
+    }
+}
+
+/// Create a new temporary file.
+///
+/// This is the async equivalent of [`tempfile()`].
+///
+/// [`tempfile()`]: ../fn.tempfile.html
+pub async fn tempfile() -> io::Result<File> {
+    unblock(crate::tempfile).await.map(File::from_std)
+}
+
+/// Create a new temporary file in the specified directory.
+///
+/// This is the async equivalent of [`tempfile_in()`].
+///
+/// [`tempfile_in()`]: ../fn.tempfile_in.html
+pub async fn tempfile_in<P: AsRef<Path>>(dir: P) -> io::Result<File> {
+    let dir = dir.as_ref().to_owned();
+    unblock(move || crate::tempfile_in(dir))
+        .await
+        .map(File::from_std)
+}
+
+/// An async named temporary file.
+///
+/// This is the async equivalent of [`NamedTempFile`], and has the same
+/// [security] and [resource leaking] caveats. The file is still deleted
+/// synchronously when dropped; use [`NamedTempFile::close`] to delete it
+/// without blocking.
+///
+/// [`NamedTempFile`]: ../struct.NamedTempFile.html
+/// [security]: ../struct.NamedTempFile.html#security
+/// [resource leaking]: ../struct.NamedTempFile.html#resource-leaking
+/// [`NamedTempFile::close`]: #method.close
+pub struct NamedTempFile {
+    path: TempPath,
+    file: File,
+}
+
+impl fmt::Debug for NamedTempFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NamedTempFile({:?})", self.path)
+    }
+}
+
+impl AsRef<Path> for NamedTempFile {
+    #[inline]
+    fn as_ref(&self) -> &Path {
+        self.path()
+    }
+}
+
+/// Error returned when persisting an async temporary file fails.
+pub struct PersistError {
+    /// The underlying IO error.
+    pub error: io::Error,
+    /// The temporary file that couldn't be persisted.
+    pub file: NamedTempFile,
+    /// The path the temporary file was being persisted to.
+    pub destination: PathBuf,
+}
+
+impl fmt::Debug for PersistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PersistError({:?})", self.error)
+    }
+}
+
+impl From<PersistError> for io::Error {
+    #[inline]
+    fn from(error: PersistError) -> io::Error {
+        crate::error::persist_error(error.error, error.file.path(), &error.destination)
+    }
+}
+
+impl From<PersistError> for NamedTempFile {
+    #[inline]
+    fn from(error: PersistError) -> NamedTempFile {
+        error.file
+    }
+}
+
+impl fmt::Display for PersistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "failed to persist temporary file {:?} to {:?}: {}",
+            self.file.path(),
+            self.destination,
+            self.error
+        )
+    }
+}
+
+impl error::Error for PersistError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl NamedTempFile {
+    /// Create a new named temporary file.
+    ///
+    /// See [`NamedTempFile::new`](../struct.NamedTempFile.html#method.new).
+    pub async fn new() -> io::Result<NamedTempFile> {
+        unblock(crate::NamedTempFile::new)
+            .await
+            .map(NamedTempFile::from_std)
+    }
+
+    /// Create a new named temporary file in the specified directory.
+    ///
+    /// See [`NamedTempFile::new_in`](../struct.NamedTempFile.html#method.new_in).
+    pub async fn new_in<P: AsRef<Path>>(dir: P) -> io::Result<NamedTempFile> {
+        let dir = dir.as_ref().to_owned();
+        unblock(move || crate::NamedTempFile::new_in(dir))
+            .await
+            .map(NamedTempFile::from_std)
+    }
+
+    /// Convert a synchronous `NamedTempFile`, e.g. one created with a
+    /// [`Builder`](../struct.Builder.html), into an async one.
+    pub fn from_std(file: crate::NamedTempFile) -> NamedTempFile {
+        let (file, path) = file.into_parts();
+        NamedTempFile {
+            path,
+            file: File::from_std(file),
+        }
+    }
+
+    /// Get the temporary file's path.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Close and remove the temporary file.
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be deleted, `Err` is returned.
+    pub async fn close(self) -> io::Result<()> {
+        let NamedTempFile { path, .. } = self;
+        unblock(move || path.close()).await
+    }
+
+    /// Persist the temporary file at the target path.
+    ///
+    /// See [`NamedTempFile::persist`](../struct.NamedTempFile.html#method.persist).
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be moved to the new location, `Err` is returned.
+    pub async fn persist<P: AsRef<Path>>(self, new_path: P) -> Result<File, PersistError> {
+        let new_path = new_path.as_ref().to_owned();
+        self.persist_with(move |path| path.persist(new_path)).await
+    }
+
+    /// Persist the temporary file at the target path if and only if no file
+    /// exists there.
+    ///
+    /// See [`NamedTempFile::persist_noclobber`](../struct.NamedTempFile.html#method.persist_noclobber).
+    ///
+    /// # Errors
+    ///
+    /// If the file cannot be moved to the new location or a file already
+    /// exists there, `Err` is returned.
+    pub async fn persist_noclobber<P: AsRef<Path>>(
+        self,
+        new_path: P,
+    ) -> Result<File, PersistError> {
+        let new_path = new_path.as_ref().to_owned();
+        self.persist_with(move |path| path.persist_noclobber(new_path))
+            .await
+    }
+
+    async fn persist_with<F>(self, f: F) -> Result<File, PersistError>
+    where
+        F: FnOnce(TempPath) -> Result<(), PathPersistError> + Send + 'static,
+    {
+        let NamedTempFile { path, file } = self;
+        match unblock(move || f(path)).await {
+            Ok(()) => Ok(file),
+            Err(PathPersistError {
+                error,
+                path,
+                destination,
+            }) => Err(PersistError {
+                error,
+                file: NamedTempFile { path, file },
+                destination,
+            }),
+        }
+    }
+
+    /// Get a reference to the underlying file.
+    pub fn as_file(&self) -> &File {
+        &self.file
+    }
+
+    /// Get a mutable reference to the underlying file.
+    pub fn as_file_mut(&mut self) -> &mut File {
+        &mut self.file
+    }
+
+    /// Convert the temporary file into a `tokio::fs::File`.
+    ///
+    /// The inner file will be deleted.
+    pub fn into_file(self) -> File {
+        self.file
+    }
+
+    /// Closes the file, leaving only the temporary file path.
+    pub fn into_temp_path(self) -> TempPath {
+        self.path
+    }
+
+    /// Converts the named temporary file into its constituent parts.
+    pub fn into_parts(self) -> (File, TempPath) {
+        (self.file, self.path)
+    }
+}
+
+impl AsyncRead for NamedTempFile {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.file).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for NamedTempFile {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.file).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.file).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.file).poll_shutdown(cx)
+    }
+}
+
+impl AsyncSeek for NamedTempFile {
+    fn start_seek(mut self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        Pin::new(&mut self.file).start_seek(position)
+    }
+
+    fn poll_complete(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Pin::new(&mut self.file).poll_complete(cx)
+    }
+}
+
+/// An async temporary directory.
+///
+/// This is the async equivalent of [`TempDir`]. When dropped inside a tokio
+/// runtime, the directory is removed on the blocking thread pool, so errors
+/// are ignored and the directory may still exist for a short while after the
+/// drop. Use [`TempDir::close`] to wait for the removal and detect errors.
+///
+/// [`TempDir`]: ../struct.TempDir.html
+/// [`TempDir::close`]: #method.close
+#[derive(Debug)]
+pub struct TempDir {
+    inner: Option<crate::TempDir>,
+}
+
+impl TempDir {
+    /// Attempts to make a temporary directory inside of `env::temp_dir()`.
+    ///
+    /// See [`TempDir::new`](../struct.TempDir.html#method.new).
+    pub async fn new() -> io::Result<TempDir> {
+        unblock(crate::TempDir::new).await.map(TempDir::from)
+    }
+
+    /// Attempts to make a temporary directory inside of `dir`.
+    ///
+    /// See [`TempDir::new_in`](../struct.TempDir.html#method.new_in).
+    pub async fn new_in<P: AsRef<Path>>(dir: P) -> io::Result<TempDir> {
+        let dir = dir.as_ref().to_owned();
+        unblock(move || crate::TempDir::new_in(dir))
+            .await
+            .map(TempDir::from)
+    }
+
+    /// Accesses the path to the temporary directory.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        self.inner.as_ref().expect("TempDir already closed").path()
+    }
+
+    /// Persist the temporary directory to disk, returning the path where it
+    /// is located.
+    #[must_use]
+    pub fn into_path(mut self) -> PathBuf {
+        self.take().into_path()
+    }
+
+    /// Closes and removes the temporary directory, returning a `Result`.
+    ///
+    /// # Errors
+    ///
+    /// See [`TempDir::close`](../struct.TempDir.html#method.close).
+    pub async fn close(mut self) -> io::Result<()> {
+        let inner = self.take();
+        unblock(move || inner.close()).await
+    }
+
+    fn take(&mut self) -> crate::TempDir {
+        self.inner.take().expect("TempDir already closed")
+    }
+}
+
+impl From<crate::TempDir> for TempDir {
+    fn from(dir: crate::TempDir) -> TempDir {
+        TempDir { inner: Some(dir) }
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        self.path()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if let Some(inner) = self.inner.take() {
+            match Handle::try_current() {
+                Ok(handle) => {
+                    drop(handle.spawn_blocking(move || drop(inner)));
+                }
+                Err(_) => drop(inner),
+            }
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::future::{poll_fn, Future};
+use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
+use std::mem;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use ::tokio::fs::File;
+use ::tokio::io::{AsyncRead, AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+
+use super::tempfile;
+
+type Rolling = Pin<Box<dyn Future<Output = (Cursor<Vec<u8>>, io::Result<File>)> + Send>>;
+
+enum State {
+    InMemory(Cursor<Vec<u8>>),
+    Rolling(Rolling),
+    OnDisk(File),
+}
+
+/// An async object that behaves like a regular temporary file, but keeps
+/// data in memory until it reaches a configured size, at which point the data
+/// is written to a temporary file on disk, and further operations use the
+/// file on disk.
+///
+/// This is the async equivalent of [`SpooledTempFile`]. While the data is in
+/// memory, reads, writes and seeks complete immediately. Rolling over to disk
+/// and all subsequent I/O happen asynchronously.
+///
+/// [`SpooledTempFile`]: ../struct.SpooledTempFile.html
+pub struct SpooledTempFile {
+    max_size: usize,
+    state: State,
+}
+
+impl fmt::Debug for SpooledTempFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match self.state {
+            State::InMemory(_) => "InMemory",
+            State::Rolling(_) => "Rolling",
+            State::OnDisk(_) => "OnDisk",
+        };
+        f.debug_struct("SpooledTempFile")
+            .field("max_size", &self.max_size)
+            .field("state", &state)
+            .finish()
+    }
+}
+
+/// Create a new async spooled temporary file.
+///
+/// See [`spooled_tempfile`](../fn.spooled_tempfile.html).
+#[inline]
+pub fn spooled_tempfile(max_size: usize) -> SpooledTempFile {
+    SpooledTempFile::new(max_size)
+}
+
+impl SpooledTempFile {
+    /// Create a new async spooled temporary file, which keeps up to
+    /// `max_size` bytes in memory before rolling over to disk.
+    ///
+    /// See [`SpooledTempFile::new`](../struct.SpooledTempFile.html#method.new).
+    #[must_use]
+    pub fn new(max_size: usize) -> SpooledTempFile {
+        SpooledTempFile {
+            max_size,
+            state: State::InMemory(Cursor::new(Vec::new())),
+        }
+    }
+
+    /// Returns true if the file has been rolled over to disk, or is in the
+    /// process of being rolled over.
+    #[must_use]
+    pub fn is_rolled(&self) -> bool {
+        match self.state {
+            State::InMemory(_) => false,
+            State::Rolling(_) | State::OnDisk(_) => true,
+        }
+    }
+
+    /// Rolls over to a file on disk, regardless of current size. Does nothing
+    /// if already rolled over.
+    pub async fn roll(&mut self) -> io::Result<()> {
+        self.start_roll();
+        poll_fn(|cx| self.poll_roll(cx)).await
+    }
+
+    /// Truncates or extends the file to `size` bytes, rolling over to disk if
+    /// `size` exceeds the configured maximum size.
+    ///
+    /// Any in-flight write to the file on disk is flushed first.
+    pub async fn set_len(&mut self, size: u64) -> io::Result<()> {
+        if size as usize > self.max_size {
+            self.roll().await?; // does nothing if already rolled over
+        }
+        poll_fn(|cx| self.poll_roll(cx)).await?;
+        match self.state {
+            State::InMemory(ref mut cursor) => {
+                cursor.get_mut().resize(size as usize, 0);
+                Ok(())
+            }
+            State::OnDisk(ref mut file) => {
+                // Wait for any in-flight write before truncating.
+                file.flush().await?;
+                file.set_len(size).await
+            }
+            State::Rolling(_) => unreachable!("roll over still in progress"),
+        }
+    }
+
+    /// Starts rolling the in-memory data over to disk, if it's in memory.
+    fn start_roll(&mut self) {
+        if let State::InMemory(ref mut cursor) = self.state {
+            let cursor = mem::replace(cursor, Cursor::new(Vec::new()));
+            self.state = State::Rolling(Box::pin(async move {
+                let result = async {
+                    let mut file = tempfile().await?;
+                    file.write_all(cursor.get_ref()).await?;
+                    file.seek(SeekFrom::Start(cursor.position())).await?;
+                    Ok(file)
+                }
+                .await;
+                (cursor, result)
+            }));
+        }
+    }
+
+    /// Drives an in-progress roll over to completion. If it fails, the data
+    /// stays in memory.
+    fn poll_roll(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if let State::Rolling(ref mut rolling) = self.state {
+            let (cursor, result) = ready!(rolling.as_mut().poll(cx));
+            match result {
+                Ok(file) => self.state = State::OnDisk(file),
+                Err(e) => {
+                    self.state = State::InMemory(cursor);
+                    return Poll::Ready(Err(e));
+                }
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncRead for SpooledTempFile {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_roll(cx))?;
+        match this.state {
+            State::InMemory(ref mut cursor) => {
+                let n = Read::read(cursor, buf.initialize_unfilled())?;
+                buf.advance(n);
+                Poll::Ready(Ok(()))
+            }
+            State::OnDisk(ref mut file) => Pin::new(file).poll_read(cx, buf),
+            State::Rolling(_) => unreachable!("roll over still in progress"),
+        }
+    }
+}
+
+impl AsyncWrite for SpooledTempFile {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        // roll over to file if necessary
+        if let State::InMemory(ref cursor) = this.state {
+            let end = usize::try_from(cursor.position())
+                .ok()
+                .and_then(|position| position.checked_add(buf.len()));
+            if !matches!(end, Some(end) if end <= this.max_size) {
+                this.start_roll();
+            }
+        }
+        ready!(this.poll_roll(cx))?;
+
+        // write the bytes
+        match this.state {
+            State::InMemory(ref mut cursor) => Poll::Ready(Write::write(cursor, buf)),
+            State::OnDisk(ref mut file) => Pin::new(file).poll_write(cx, buf),
+            State::Rolling(_) => unreachable!("roll over still in progress"),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_roll(cx))?;
+        match this.state {
+            State::InMemory(_) => Poll::Ready(Ok(())),
+            State::OnDisk(ref mut file) => Pin::new(file).poll_flush(cx),
+            State::Rolling(_) => unreachable!("roll over still in progress"),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_roll(cx))?;
+        match this.state {
+            State::InMemory(_) => Poll::Ready(Ok(())),
+            State::OnDisk(ref mut file) => Pin::new(file).poll_shutdown(cx),
+            State::Rolling(_) => unreachable!("roll over still in progress"),
+        }
+    }
+}
+
+impl AsyncSeek for SpooledTempFile {
+    #[allow(clippy::io_other_error)]
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        match self.get_mut().state {
+            State::InMemory(ref mut cursor) => Seek::seek(cursor, position).map(|_| ()),
+            State::OnDisk(ref mut file) => Pin::new(file).start_seek(position),
+            State::Rolling(_) => Err(io::Error::new(
+                io::ErrorKind::Other,
+                "other file operation is pending, call poll_complete before start_seek",
+            )),
+        }
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        let this = self.get_mut();
+        ready!(this.poll_roll(cx))?;
+        match this.state {
+            State::InMemory(ref cursor) => Poll::Ready(Ok(cursor.position())),
+            State::OnDisk(ref mut file) => Pin::new(file).poll_complete(cx),
+            State::Rolling(_) => unreachable!("roll over still in progress"),
+        }
+    }
+}
@@ -2,7 +2,7 @@ use std::ffi::{OsStr, OsString};
 use std::path::{Path, PathBuf};
 use std::{io, iter::repeat_with};
 
-use crate::error::IoResultExt;
+use crate::error::{IoResultExt, Operation};
 
 fn tmpname(prefix: &OsStr, suffix: &OsStr, rand_len: usize) -> OsString {
     let mut buf = OsString::with_capacity(prefix.len() + suffix.len() + rand_len);
@@ -46,5 +46,5 @@ where
         io::ErrorKind::AlreadyExists,
         "too many temporary files exist",
     ))
-    .with_err_path(|| base)
+    .with_err_path(Operation::Create, || base)
 }
@@ -0,0 +1,208 @@
+#![deny(rust_2018_idioms)]
+
+use std::error::Error as StdError;
+use std::fs;
+use std::io;
+
+use tempfile::{Builder, Error, NamedTempFile, Operation, TempDir};
+
+#[test]
+fn test_create_error() {
+    let dir = TempDir::new().unwrap();
+    let missing = dir.path().join("missing");
+    let err = Builder::new().tempfile_in(&missing).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+    let err = Error::from_io_error(&err).unwrap();
+    assert_eq!(err.operation(), Operation::Create);
+    assert_eq!(err.path().parent().unwrap(), missing);
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    assert_eq!(err.io_error().kind(), io::ErrorKind::NotFound);
+
+    // The IO error is part of the message, and the source.
+    assert_eq!(
+        err.to_string(),
+        format!("failed to create {:?}: {}", err.path(), err.io_error())
+    );
+    let source = std::error::Error::source(err).unwrap();
+    assert_eq!(source.to_string(), err.io_error().to_string());
+}
+
+#[test]
+fn test_tempdir_create_error() {
+    let dir = TempDir::new().unwrap();
+    let missing = dir.path().join("missing");
+    let err = TempDir::new_in(&missing).unwrap_err();
+    let err = Error::from_io_error(&err).unwrap();
+    assert_eq!(err.operation(), Operation::Create);
+    assert!(err.path().starts_with(&missing));
+}
+
+#[test]
+fn test_persist_error() {
+    let dir = TempDir::new().unwrap();
+    let dest = dir.path().join("missing").join("dest");
+    let tmp = NamedTempFile::new_in(dir.path()).unwrap();
+    let tmp_path = tmp.path().to_path_buf();
+    let err = tmp.persist(&dest).unwrap_err();
+
+    // The OS error is returned as is, and the paths are recorded alongside.
+    assert!(Error::from_io_error(&err.error).is_none());
+    assert!(err.error.raw_os_error().is_some());
+    assert_eq!(err.error.kind(), io::ErrorKind::NotFound);
+    assert_eq!(err.file.path(), tmp_path);
+    assert_eq!(err.destination, dest);
+
+    // Converting it into an `io::Error` wraps it with both paths.
+    let err = io::Error::from(err);
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    let err = Error::from_io_error(&err).unwrap();
+    assert_eq!(err.operation(), Operation::Persist);
+    assert_eq!(err.path(), tmp_path);
+    assert_eq!(err.destination(), Some(dest.as_path()));
+    assert!(err.io_error().raw_os_error().is_some());
+}
+
+#[test]
+fn test_persist_noclobber_error() {
+    let dir = TempDir::new().unwrap();
+    let dest = dir.path().join("dest");
+    fs::write(&dest, b"existing").unwrap();
+    let tmp = NamedTempFile::new_in(dir.path()).unwrap().into_temp_path();
+
+    let err = tmp.persist_noclobber(&dest).unwrap_err();
+    assert!(err.error.raw_os_error().is_some());
+    assert_eq!(err.error.kind(), io::ErrorKind::AlreadyExists);
+    assert_eq!(err.destination, dest);
+    assert_eq!(fs::read(&dest).unwrap(), b"existing");
+
+    let tmp_path = err.path.to_path_buf();
+    let err = io::Error::from(err);
+    let err = Error::from_io_error(&err).unwrap();
+    assert_eq!(err.operation(), Operation::Persist);
+    assert_eq!(err.path(), tmp_path);
+    assert_eq!(err.destination(), Some(dest.as_path()));
+    assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+}
+
+/// Collects the messages of an error and all of its sources.
+fn chain(err: &(dyn StdError + 'static)) -> Vec<String> {
+    let mut messages = vec![err.to_string()];
+    let mut source = err.source();
+    while let Some(err) = source {
+        messages.push(err.to_string());
+        source = err.source();
+    }
+    messages
+}
+
+#[test]
+fn test_io_error_display() {
+    let dir = TempDir::new().unwrap();
+    let missing = dir.path().join("missing");
+    let err = Builder::new().tempfile_in(&missing).unwrap_err();
+
+    // Plain `Display` of the returned `io::Error` includes the OS error.
+    let inner = Error::from_io_error(&err).unwrap();
+    let os_error = io::Error::from_raw_os_error(inner.io_error().raw_os_error().unwrap());
+    assert_eq!(
+        err.to_string(),
+        format!("failed to create {:?}: {}", inner.path(), os_error)
+    );
+}
+
+#[test]
+fn test_error_chain() {
+    let dir = TempDir::new().unwrap();
+    let missing = dir.path().join("missing");
+    let err = Builder::new().tempfile_in(&missing).unwrap_err();
+
+    let inner = Error::from_io_error(&err).unwrap();
+    let os_message = inner.io_error().to_string();
+    let messages = chain(inner);
+    assert_eq!(
+        messages,
+        vec![
+            format!("failed to create {:?}: {}", inner.path(), os_message),
+            os_message,
+        ]
+    );
+
+    // The `io::Error` itself displays the same message and shares the chain.
+    assert_eq!(chain(&err), messages);
+}
+
+#[test]
+fn test_persist_error_chain() {
+    let dir = TempDir::new().unwrap();
+    let dest = dir.path().join("missing").join("dest");
+    let tmp = NamedTempFile::new_in(dir.path()).unwrap();
+    let tmp_path = tmp.path().to_path_buf();
+    let err = tmp.persist(&dest).unwrap_err();
+    let os_message = err.error.to_string();
+
+    let messages = chain(&err);
+    assert_eq!(
+        messages,
+        vec![
+            format!(
+                "failed to persist temporary file {:?} to {:?}: {}",
+                tmp_path, dest, os_message
+            ),
+            os_message.clone(),
+        ]
+    );
+
+    let err = err.file.into_temp_path().persist(&dest).unwrap_err();
+    let messages = chain(&err);
+    assert_eq!(
+        messages,
+        vec![
+            format!(
+                "failed to persist temporary file path {:?} to {:?}: {}",
+                tmp_path, dest, os_message
+            ),
+            os_message.clone(),
+        ]
+    );
+
+    // Converted into an `io::Error`, both paths and the OS error show up.
+    let err = io::Error::from(err);
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "failed to persist {:?} to {:?}: {}",
+            tmp_path, dest, os_message
+        )
+    );
+}
+
+#[test]
+fn test_remove_error() {
+    let tmp = NamedTempFile::new().unwrap().into_temp_path();
+    fs::remove_file(&tmp).unwrap();
+    let path = tmp.to_path_buf();
+
+    let err = tmp.close().unwrap_err();
+    let err = Error::from_io_error(&err).unwrap();
+    assert_eq!(err.operation(), Operation::Remove);
+    assert_eq!(err.path(), path);
+}
+
+#[test]
+fn test_reopen_error() {
+    let tmp = NamedTempFile::new().unwrap();
+    fs::remove_file(tmp.path()).unwrap();
+
+    let err = tmp.reopen().unwrap_err();
+    let err = Error::from_io_error(&err).unwrap();
+    assert_eq!(err.operation(), Operation::Reopen);
+    assert_eq!(err.path(), tmp.path());
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+}
+
+#[test]
+fn test_not_a_path_error() {
+    let err = io::Error::new(io::ErrorKind::InvalidData, "invalid");
+    assert!(Error::from_io_error(&err).is_none());
+}
@@ -0,0 +1,35 @@
+#![deny(rust_2018_idioms)]
+#![cfg(unix)]
+
+use std::io::{Seek, SeekFrom, Write};
+use std::os::unix::io::AsRawFd;
+use std::process::Command;
+
+use tempfile::{tempfile, InheritableFile};
+
+#[test]
+fn test_path() {
+    let file = tempfile().unwrap();
+    let inheritable = InheritableFile::new(&file).unwrap();
+    assert_ne!(inheritable.as_raw_fd(), file.as_raw_fd());
+    assert_eq!(
+        inheritable.path().to_str().unwrap(),
+        format!("/dev/fd/{}", inheritable.as_raw_fd())
+    );
+    assert_eq!(inheritable.to_command_arg(), inheritable.path().as_os_str());
+}
+
+#[test]
+fn test_child_reads_unnamed_file() {
+    let mut file = tempfile().unwrap();
+    file.write_all(b"abcde").unwrap();
+    file.seek(SeekFrom::Start(0)).unwrap();
+
+    let inheritable = InheritableFile::new(&file).unwrap();
+    let output = Command::new("cat")
+        .arg(inheritable.to_command_arg())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"abcde");
+}
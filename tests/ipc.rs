@@ -0,0 +1,95 @@
+#![deny(rust_2018_idioms)]
+#![cfg(unix)]
+
+use std::io::{Read, Write};
+use std::os::unix::net::{UnixDatagram, UnixStream};
+use std::path::Path;
+
+use tempfile::{Builder, TempDir, TempRoot};
+
+#[test]
+fn test_unix_listener() {
+    let listener = Builder::new().prefix("sock").unix_listener().unwrap();
+    let path = listener.path().to_path_buf();
+    assert!(path
+        .file_name()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .starts_with("sock"));
+
+    let mut client = UnixStream::connect(&path).unwrap();
+    let (mut server, _) = listener.as_file().accept().unwrap();
+    client.write_all(b"ping").unwrap();
+    let mut buf = [0; 4];
+    server.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"ping");
+
+    drop(listener);
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_unix_listener_long_prefix() {
+    let prefix = "x".repeat(60);
+    let listener = Builder::new().prefix(&prefix).unix_listener().unwrap();
+    UnixStream::connect(listener.path()).unwrap();
+}
+
+#[cfg(not(target_os = "android"))]
+#[test]
+fn test_unix_listener_long_temp_dir() {
+    let dir = TempDir::new().unwrap();
+    let mut deep = dir.path().to_path_buf();
+    while deep.as_os_str().len() < 120 {
+        deep.push("x".repeat(20));
+    }
+    std::fs::create_dir_all(&deep).unwrap();
+
+    let listener = Builder::new()
+        .root(TempRoot::new().candidate(&deep))
+        .unix_listener()
+        .unwrap();
+    // The socket path wouldn't fit in `sockaddr_un`, so `/tmp` is used.
+    assert_eq!(listener.path().parent().unwrap(), Path::new("/tmp"));
+    UnixStream::connect(listener.path()).unwrap();
+}
+
+#[test]
+fn test_unix_listener_in() {
+    let dir = TempDir::new().unwrap();
+    let listener = Builder::new().unix_listener_in(dir.path()).unwrap();
+    assert_eq!(listener.path().parent().unwrap(), dir.path());
+    UnixStream::connect(listener.path()).unwrap();
+}
+
+#[test]
+fn test_unix_datagram() {
+    let socket = Builder::new().unix_datagram().unwrap();
+    let path = socket.path().to_path_buf();
+
+    let client = UnixDatagram::unbound().unwrap();
+    client.send_to(b"ping", &path).unwrap();
+    let mut buf = [0; 4];
+    assert_eq!(socket.as_file().recv(&mut buf).unwrap(), 4);
+    assert_eq!(&buf, b"ping");
+
+    drop(socket);
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_fifo() {
+    use std::fs;
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+    let dir = TempDir::new().unwrap();
+    let fifo = Builder::new().suffix(".fifo").fifo_in(dir.path()).unwrap();
+    let path = fifo.to_path_buf();
+    let meta = fs::metadata(&path).unwrap();
+    assert!(meta.file_type().is_fifo());
+    assert_eq!(meta.permissions().mode() & 0o777, 0o600);
+
+    drop(fifo);
+    assert!(!path.exists());
+}
@@ -0,0 +1,76 @@
+#![deny(rust_2018_idioms)]
+
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use tempfile::{Builder, NamedTempFile, SizeLimitExceeded, SizeLimited, SpooledTempFile};
+
+fn assert_exceeded(err: io::Error, limit: u64) {
+    let exceeded = SizeLimitExceeded::from_io_error(&err).unwrap();
+    assert_eq!(exceeded.limit(), limit);
+    assert_eq!(
+        err.to_string(),
+        format!("size limit of {} bytes exceeded", limit)
+    );
+}
+
+#[test]
+fn test_named() {
+    let mut file = SizeLimited::new(NamedTempFile::new().unwrap(), 8).unwrap();
+    file.write_all(b"abcde").unwrap();
+    assert_exceeded(file.write(b"fghi").unwrap_err(), 8);
+    // Nothing was written.
+    assert_eq!(file.get_ref().as_file().metadata().unwrap().len(), 5);
+
+    file.write_all(b"fgh").unwrap();
+    assert_exceeded(file.write_all(b"i").unwrap_err(), 8);
+
+    // Overwriting is fine.
+    file.seek(SeekFrom::Start(0)).unwrap();
+    file.write_all(b"ABCDEFGH").unwrap();
+    let mut buf = String::new();
+    file.seek(SeekFrom::Start(0)).unwrap();
+    file.read_to_string(&mut buf).unwrap();
+    assert_eq!(buf, "ABCDEFGH");
+}
+
+#[test]
+fn test_spooled() {
+    let mut spool = SpooledTempFile::new(4);
+    spool.write_all(b"abc").unwrap();
+
+    let mut spool = SizeLimited::new(spool, 6).unwrap();
+    spool.write_all(b"def").unwrap();
+    assert_exceeded(spool.write_all(b"g").unwrap_err(), 6);
+    assert_eq!(spool.limit(), 6);
+    assert_eq!(spool.into_inner().into_bytes().unwrap(), b"abcdef");
+}
+
+#[test]
+fn test_already_exceeded() {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(b"abcde").unwrap();
+    assert_exceeded(SizeLimited::new(file, 4).unwrap_err(), 4);
+}
+
+#[test]
+fn test_append() {
+    let file = Builder::new().append(true).tempfile().unwrap();
+    let mut file = SizeLimited::new_append(file, 8).unwrap();
+    file.write_all(b"abcde").unwrap();
+
+    // Seeking back doesn't make room: appends still go to the end.
+    file.seek(SeekFrom::Start(0)).unwrap();
+    assert_exceeded(file.write(b"fghi").unwrap_err(), 8);
+    assert_eq!(file.get_ref().as_file().metadata().unwrap().len(), 5);
+
+    file.seek(SeekFrom::Start(0)).unwrap();
+    file.write_all(b"fgh").unwrap();
+    assert_eq!(file.get_ref().as_file().metadata().unwrap().len(), 8);
+    file.seek(SeekFrom::Start(0)).unwrap();
+    assert_exceeded(file.write_all(b"i").unwrap_err(), 8);
+
+    let mut buf = String::new();
+    file.seek(SeekFrom::Start(0)).unwrap();
+    file.read_to_string(&mut buf).unwrap();
+    assert_eq!(buf, "abcdefgh");
+}
@@ -0,0 +1,71 @@
+#![deny(rust_2018_idioms)]
+
+use std::fs;
+use std::io::Write;
+
+use tempfile::{Builder, NamedTempFile, TempDir};
+
+#[cfg(unix)]
+#[test]
+fn test_symlink_to() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("target"), b"abcde").unwrap();
+
+    let link = Builder::new()
+        .prefix("link")
+        .symlink_to_in(dir.path(), "target")
+        .unwrap();
+    let path = link.to_path_buf();
+    assert_eq!(path.parent().unwrap(), dir.path());
+    assert_eq!(fs::read_link(&path).unwrap().to_str().unwrap(), "target");
+    assert_eq!(fs::read(&path).unwrap(), b"abcde");
+
+    drop(link);
+    assert!(fs::symlink_metadata(&path).is_err());
+    assert!(dir.path().join("target").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlink_persist_over_existing_link() {
+    use std::os::unix::fs::symlink;
+
+    let dir = TempDir::new().unwrap();
+    fs::create_dir(dir.path().join("v1")).unwrap();
+    fs::create_dir(dir.path().join("v2")).unwrap();
+    let current = dir.path().join("current");
+    symlink("v1", &current).unwrap();
+
+    let link = Builder::new().symlink_to_in(dir.path(), "v2").unwrap();
+    link.persist(&current).unwrap();
+    assert_eq!(fs::read_link(&current).unwrap().to_str().unwrap(), "v2");
+}
+
+#[test]
+fn test_hardlink_to() {
+    let mut source = NamedTempFile::new().unwrap();
+    source.write_all(b"abcde").unwrap();
+
+    let link = Builder::new().hardlink_to(source.path()).unwrap();
+    let path = link.to_path_buf();
+    assert_eq!(path.parent(), source.path().parent());
+    assert_eq!(fs::read(&path).unwrap(), b"abcde");
+
+    drop(link);
+    assert!(!path.exists());
+    assert!(source.path().exists());
+}
+
+#[test]
+fn test_hardlink_persist() {
+    let dir = TempDir::new().unwrap();
+    let source = dir.path().join("source");
+    let dest = dir.path().join("dest");
+    fs::write(&source, b"new").unwrap();
+    fs::write(&dest, b"old").unwrap();
+
+    let link = Builder::new().hardlink_to_in(dir.path(), &source).unwrap();
+    link.persist(&dest).unwrap();
+    assert_eq!(fs::read(&dest).unwrap(), b"new");
+    assert_eq!(fs::read(&source).unwrap(), b"new");
+}
@@ -0,0 +1,84 @@
+#![deny(rust_2018_idioms)]
+#![cfg(any(target_os = "android", target_os = "linux"))]
+
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::os::unix::io::AsRawFd;
+
+use tempfile::{memfd, MemfdExt, MemfdOptions};
+
+#[test]
+fn test_basic() {
+    let mut file = memfd("basic").unwrap();
+    file.write_all(b"abcde").unwrap();
+    file.seek(SeekFrom::Start(0)).unwrap();
+    let mut buf = String::new();
+    file.read_to_string(&mut buf).unwrap();
+    assert_eq!("abcde", buf);
+}
+
+#[test]
+fn test_name() {
+    let file = memfd("my-memfd").unwrap();
+    let link = fs::read_link(format!("/proc/self/fd/{}", file.as_raw_fd())).unwrap();
+    assert!(link.to_str().unwrap().starts_with("/memfd:my-memfd"));
+}
+
+#[test]
+fn test_options() {
+    let mut file = MemfdOptions::new()
+        .cloexec(false)
+        .allow_sealing(true)
+        .create("options")
+        .unwrap();
+    file.write_all(b"abcde").unwrap();
+    assert_eq!(file.metadata().unwrap().len(), 5);
+}
+
+#[test]
+fn test_bad_name() {
+    assert!(memfd("bad\0name").is_err());
+}
+
+#[test]
+fn test_seals() {
+    let mut file = MemfdOptions::new()
+        .allow_sealing(true)
+        .create("seals")
+        .unwrap();
+    file.write_all(b"abcde").unwrap();
+    let seals = file.seals().unwrap();
+    assert!(!seals.write() && !seals.shrink() && !seals.grow() && !seals.seal());
+
+    file.seal_shrink().unwrap();
+    let seals = file.seals().unwrap();
+    assert!(seals.shrink());
+    assert!(!seals.grow());
+    assert!(!seals.write());
+    assert!(file.set_len(2).is_err());
+    file.set_len(10).unwrap();
+
+    file.seal_grow().unwrap();
+    assert!(file.set_len(20).is_err());
+    file.seek(SeekFrom::Start(0)).unwrap();
+    file.write_all(b"ABCDE").unwrap();
+
+    file.seal_write().unwrap();
+    file.seal_seal().unwrap();
+    let seals = file.seals().unwrap();
+    assert!(seals.write());
+    assert!(seals.seal());
+    file.seek(SeekFrom::Start(0)).unwrap();
+    assert!(file.write_all(b"abcde").is_err());
+
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf).unwrap();
+    assert_eq!(buf.as_slice(), b"ABCDE\0\0\0\0\0");
+}
+
+#[test]
+fn test_seals_not_allowed() {
+    let file = memfd("no-seals").unwrap();
+    assert!(file.seals().unwrap().seal());
+    assert!(file.seal_write().is_err());
+}
@@ -3,7 +3,7 @@
 use std::env;
 use std::ffi::{OsStr, OsString};
 use std::fs::File;
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 use tempfile::{tempdir, Builder, NamedTempFile, TempPath};
 
@@ -439,3 +439,379 @@ fn test_make_uds_conflict() {
         assert!(socket.path().exists());
     }
 }
+
+#[test]
+fn test_to_command_arg() {
+    let tmpfile = NamedTempFile::new_in(".").unwrap();
+    let arg = tmpfile.to_command_arg();
+    assert!(Path::new(&arg).is_absolute());
+    assert_eq!(arg, tmpfile.path().as_os_str());
+}
+
+#[test]
+fn test_copy_of() {
+    let dir = tempdir().unwrap();
+    let original = dir.path().join("original");
+    std::fs::write(&original, b"abcde").unwrap();
+
+    let mut copy = Builder::new().prefix("copy").copy_of(&original).unwrap();
+    assert_eq!(copy.path().parent().unwrap(), dir.path());
+    let mut buf = String::new();
+    copy.read_to_string(&mut buf).unwrap();
+    assert_eq!(buf, "abcde");
+
+    copy.write_all(b"fgh").unwrap();
+    assert_eq!(std::fs::read(&original).unwrap(), b"abcde");
+    copy.persist(&original).unwrap();
+    assert_eq!(std::fs::read(&original).unwrap(), b"abcdefgh");
+}
+
+#[test]
+fn test_copy_of_append() {
+    let dir = tempdir().unwrap();
+    let original = dir.path().join("original");
+    std::fs::write(&original, b"abcde").unwrap();
+
+    let mut copy = Builder::new().append(true).copy_of(&original).unwrap();
+    let mut buf = String::new();
+    copy.read_to_string(&mut buf).unwrap();
+    assert_eq!(buf, "abcde");
+
+    // Writes still go to the end.
+    copy.seek(SeekFrom::Start(0)).unwrap();
+    copy.write_all(b"fgh").unwrap();
+    copy.persist(&original).unwrap();
+    assert_eq!(std::fs::read(&original).unwrap(), b"abcdefgh");
+}
+
+#[test]
+fn test_copy_of_keeps_length() {
+    let dir = tempdir().unwrap();
+    let original = dir.path().join("original");
+    std::fs::write(&original, b"abcde").unwrap();
+
+    let mut copy = Builder::new().sparse(100).copy_of(&original).unwrap();
+    let mut buf = Vec::new();
+    copy.read_to_end(&mut buf).unwrap();
+    assert_eq!(buf, b"abcde");
+
+    let copy = Builder::new().preallocate(4096).copy_of(&original).unwrap();
+    assert_eq!(copy.as_file().metadata().unwrap().len(), 5);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_clone_from_preserves_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir().unwrap();
+    let original = dir.path().join("script.sh");
+    std::fs::write(&original, b"#!/bin/sh\n").unwrap();
+    std::fs::set_permissions(&original, std::fs::Permissions::from_mode(0o751)).unwrap();
+
+    let copy = NamedTempFile::clone_from(&original).unwrap();
+    let mode = copy.as_file().metadata().unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o751);
+}
+
+#[test]
+fn test_copy_of_missing() {
+    let dir = tempdir().unwrap();
+    let err = Builder::new()
+        .copy_of(dir.path().join("missing"))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmpfile = Builder::new()
+        .permissions(std::fs::Permissions::from_mode(0o400))
+        .tempfile()
+        .unwrap();
+    let mode = tmpfile.as_file().metadata().unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o400);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_custom_flags() {
+    fn open_flags(file: &File) -> i32 {
+        use std::os::unix::io::AsRawFd;
+        let flags = unsafe { libc::fcntl(file.as_raw_fd(), libc::F_GETFL) };
+        assert!(flags >= 0, "{}", io::Error::last_os_error());
+        flags
+    }
+
+    let tmpfile = Builder::new()
+        .custom_flags(libc::O_SYNC)
+        .tempfile()
+        .unwrap();
+    assert_eq!(open_flags(tmpfile.as_file()) & libc::O_SYNC, libc::O_SYNC);
+
+    let tmpfile = Builder::new()
+        .custom_flags(libc::O_SYNC)
+        .tempfile_unnamed()
+        .unwrap();
+    assert_eq!(open_flags(&tmpfile) & libc::O_SYNC, libc::O_SYNC);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_custom_flags_keep_exclusive_create() {
+    let dir = tempdir().unwrap();
+    std::fs::write(dir.path().join("existing"), b"abcde").unwrap();
+    let err = Builder::new()
+        .prefix("existing")
+        .rand_bytes(0)
+        .custom_flags(libc::O_TRUNC)
+        .tempfile_in(dir.path())
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+    assert_eq!(
+        std::fs::read(dir.path().join("existing")).unwrap(),
+        b"abcde"
+    );
+}
+
+#[test]
+fn test_preallocate() {
+    let tmpfile = Builder::new().preallocate(1 << 20).tempfile().unwrap();
+    let meta = tmpfile.as_file().metadata().unwrap();
+    assert_eq!(meta.len(), 1 << 20);
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::fs::MetadataExt;
+        assert!(meta.blocks() * 512 >= 1 << 20);
+    }
+
+    let tmpfile = Builder::new().preallocate(4096).tempfile_unnamed().unwrap();
+    assert_eq!(tmpfile.metadata().unwrap().len(), 4096);
+}
+
+#[test]
+fn test_allocate_zero() {
+    let mut tmpfile = NamedTempFile::new().unwrap();
+    tmpfile.allocate(0).unwrap();
+    assert_eq!(tmpfile.as_file().metadata().unwrap().len(), 0);
+    tmpfile.write_all(b"abcde").unwrap();
+    tmpfile.allocate(0).unwrap();
+    assert_eq!(tmpfile.as_file().metadata().unwrap().len(), 5);
+
+    let tmpfile = Builder::new().preallocate(0).tempfile().unwrap();
+    assert_eq!(tmpfile.as_file().metadata().unwrap().len(), 0);
+}
+
+#[test]
+fn test_allocate_error() {
+    let dir = tempdir().unwrap();
+    let err = Builder::new()
+        .preallocate(u64::MAX)
+        .tempfile_in(dir.path())
+        .unwrap_err();
+    let err = tempfile::Error::from_io_error(&err).unwrap();
+    assert_eq!(err.operation(), tempfile::Operation::Allocate);
+    assert_eq!(err.path().parent().unwrap(), dir.path());
+
+    let err = Builder::new()
+        .preallocate(u64::MAX)
+        .anonymous_in(dir.path())
+        .unwrap_err();
+    let err = tempfile::Error::from_io_error(&err).unwrap();
+    assert_eq!(err.operation(), tempfile::Operation::Allocate);
+    assert_eq!(err.path(), dir.path());
+
+    let err = Builder::new()
+        .sparse(u64::MAX)
+        .anonymous_in(dir.path())
+        .unwrap_err();
+    let err = tempfile::Error::from_io_error(&err).unwrap();
+    assert_eq!(err.operation(), tempfile::Operation::Create);
+    assert_eq!(err.path(), dir.path());
+}
+
+#[test]
+fn test_allocate_never_truncates() {
+    let mut tmpfile = NamedTempFile::new().unwrap();
+    tmpfile.write_all(b"abcde").unwrap();
+    tmpfile.allocate(2).unwrap();
+    assert_eq!(tmpfile.as_file().metadata().unwrap().len(), 5);
+    tmpfile.allocate(100).unwrap();
+    assert_eq!(tmpfile.as_file().metadata().unwrap().len(), 100);
+
+    let mut buf = [0; 5];
+    tmpfile.seek(SeekFrom::Start(0)).unwrap();
+    tmpfile.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"abcde");
+}
+
+#[test]
+fn test_sparse() {
+    let mut tmpfile = Builder::new().sparse(1 << 30).tempfile().unwrap();
+    let meta = tmpfile.as_file().metadata().unwrap();
+    assert_eq!(meta.len(), 1 << 30);
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::unix::fs::MetadataExt;
+        assert_eq!(meta.blocks(), 0);
+    }
+
+    let mut buf = [1; 16];
+    tmpfile.seek(SeekFrom::Start(1 << 29)).unwrap();
+    tmpfile.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [0; 16]);
+}
+
+#[test]
+fn test_data_ranges() {
+    let mut tmpfile = Builder::new().sparse(1 << 24).tempfile().unwrap();
+    tmpfile.seek(SeekFrom::Start(1 << 20)).unwrap();
+    tmpfile.write_all(&[1; 4096]).unwrap();
+    tmpfile.seek(SeekFrom::Start(10)).unwrap();
+
+    let ranges = tmpfile.data_ranges().unwrap();
+    #[allow(clippy::seek_from_current)]
+    let pos = tmpfile.seek(SeekFrom::Current(0)).unwrap();
+    assert_eq!(pos, 10);
+    assert!(!ranges.is_empty());
+    // Every range is within the file, and the written data is covered.
+    assert!(ranges.iter().all(|r| r.start < r.end && r.end <= 1 << 24));
+    assert!(ranges
+        .iter()
+        .any(|r| r.start <= 1 << 20 && r.end >= (1 << 20) + 4096));
+
+    #[cfg(target_os = "linux")]
+    if tmpfile.seek_hole(0).unwrap() == Some(0) {
+        // The filesystem supports holes.
+        assert!(ranges.iter().all(|r| r.start >= 4096));
+        assert_eq!(tmpfile.seek_data(0).unwrap(), Some(ranges[0].start));
+    }
+
+    assert_eq!(tmpfile.seek_data(1 << 24).unwrap(), None);
+    assert_eq!(tmpfile.seek_hole(1 << 24).unwrap(), None);
+}
+
+#[test]
+fn test_data_ranges_keeps_position() {
+    let mut tmpfile = NamedTempFile::new().unwrap();
+    tmpfile.write_all(b"abcde").unwrap();
+    for &pos in &[0, 3, 5, 1 << 20] {
+        tmpfile.seek(SeekFrom::Start(pos)).unwrap();
+        assert_eq!(tmpfile.data_ranges().unwrap(), vec![0..5]);
+        #[allow(clippy::seek_from_current)]
+        let after = tmpfile.seek(SeekFrom::Current(0)).unwrap();
+        assert_eq!(after, pos);
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_punch_hole() {
+    use std::os::unix::fs::MetadataExt;
+
+    let mut tmpfile = NamedTempFile::new().unwrap();
+    tmpfile.write_all(&[1; 1 << 20]).unwrap();
+    tmpfile.as_file().sync_all().unwrap();
+    let blocks = tmpfile.as_file().metadata().unwrap().blocks();
+
+    if let Err(e) = tmpfile.punch_hole(1 << 18, 1 << 19) {
+        // Not all filesystems support punching holes.
+        let err = tempfile::Error::from_io_error(&e).unwrap();
+        assert_eq!(err.operation(), tempfile::Operation::PunchHole);
+        assert_eq!(err.io_error().raw_os_error(), Some(libc::EOPNOTSUPP));
+        return;
+    }
+    let meta = tmpfile.as_file().metadata().unwrap();
+    assert_eq!(meta.len(), 1 << 20);
+    assert!(meta.blocks() < blocks);
+
+    let mut buf = [1; 16];
+    tmpfile.seek(SeekFrom::Start(1 << 18)).unwrap();
+    tmpfile.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [0; 16]);
+    tmpfile.seek(SeekFrom::Start(0)).unwrap();
+    tmpfile.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [1; 16]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_require_free_space() {
+    let dir = tempdir().unwrap();
+    Builder::new()
+        .require_free_space(1)
+        .tempfile_in(dir.path())
+        .unwrap();
+
+    let err = Builder::new()
+        .require_free_space(u64::MAX)
+        .tempfile_in(dir.path())
+        .unwrap_err();
+    let err = tempfile::Error::from_io_error(&err).unwrap();
+    assert_eq!(err.path(), dir.path());
+    assert!(err
+        .io_error()
+        .to_string()
+        .starts_with("not enough free space"));
+
+    assert!(Builder::new()
+        .require_free_space(u64::MAX)
+        .anonymous_in(dir.path())
+        .is_err());
+    assert!(Builder::new()
+        .require_free_space(u64::MAX)
+        .tempdir_in(dir.path())
+        .is_err());
+    assert!(Builder::new()
+        .require_free_space(u64::MAX)
+        .make_in(dir.path(), |path| std::fs::File::create(path))
+        .is_err());
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+
+    Builder::new()
+        .require_free_space(1)
+        .tempdir_in(dir.path())
+        .unwrap();
+}
+
+#[test]
+fn test_new_for() {
+    let dir = tempdir().unwrap();
+    let dest = dir.path().join("dest");
+    let mut file = NamedTempFile::new_for(&dest).unwrap();
+    assert_eq!(file.path().parent().unwrap(), dir.path());
+    write!(file, "abcde").unwrap();
+    file.persist(&dest).unwrap();
+    assert_eq!(std::fs::read_to_string(&dest).unwrap(), "abcde");
+}
+
+#[test]
+fn test_tempfile_for_scratch_dir() {
+    let dir = tempdir().unwrap();
+    let scratch = dir.path().join("scratch");
+    std::fs::create_dir(&scratch).unwrap();
+    let dest = dir.path().join("dest");
+
+    let file = Builder::new()
+        .scratch_dir(&scratch)
+        .tempfile_for(&dest)
+        .unwrap();
+    assert_eq!(file.path().parent().unwrap(), scratch);
+    file.persist(&dest).unwrap();
+    assert!(exists(&dest));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_tempfile_for_scratch_dir_other_filesystem() {
+    let dir = tempdir().unwrap();
+    let file = Builder::new()
+        .scratch_dir("/proc")
+        .tempfile_for(dir.path().join("dest"))
+        .unwrap();
+    assert_eq!(file.path().parent().unwrap(), dir.path());
+}
@@ -0,0 +1,101 @@
+#![deny(rust_2018_idioms)]
+
+use std::io;
+
+use tempfile::{tempdir, Builder, TempRoot};
+
+#[test]
+fn test_first_existing_candidate() {
+    let dir = tempdir().unwrap();
+    let missing = dir.path().join("missing");
+    let mut root = TempRoot::new();
+    root.candidate(&missing).candidate(dir.path());
+    assert_eq!(root.resolve().unwrap(), dir.path());
+}
+
+#[test]
+fn test_no_candidates() {
+    let dir = tempdir().unwrap();
+    let err = TempRoot::new().resolve().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+    // A file isn't a directory.
+    let file = Builder::new().tempfile_in(dir.path()).unwrap();
+    let err = TempRoot::new()
+        .candidate(file.path())
+        .resolve()
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+}
+
+#[test]
+fn test_system_candidates() {
+    let root = TempRoot::new().system_candidates().resolve().unwrap();
+    assert_eq!(root, std::env::temp_dir());
+}
+
+#[test]
+fn test_most_free_space_prefers_first() {
+    // Both candidates are on the same filesystem, so they have the same
+    // amount of free space.
+    let a = tempdir().unwrap();
+    let b = tempdir().unwrap();
+    let mut root = TempRoot::new();
+    root.candidate(a.path())
+        .candidate(b.path())
+        .most_free_space(true);
+    assert_eq!(root.resolve().unwrap(), a.path());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_same_filesystem_as() {
+    let dir = tempdir().unwrap();
+    let mut root = TempRoot::new();
+    root.candidate("/proc")
+        .candidate(dir.path())
+        .same_filesystem_as(dir.path().join("."));
+    assert_eq!(root.resolve().unwrap(), dir.path());
+
+    let missing = dir.path().join("missing");
+    let err = TempRoot::new()
+        .candidate(dir.path())
+        .same_filesystem_as(&missing)
+        .resolve()
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    let err = tempfile::Error::from_io_error(&err).unwrap();
+    assert_eq!(err.operation(), tempfile::Operation::Metadata);
+    assert_eq!(err.path(), missing);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_not_tmpfs() {
+    // `/dev/shm` is a tmpfs wherever it exists.
+    let mut root = TempRoot::new();
+    root.candidate("/dev/shm").not_tmpfs(true);
+    assert!(root.resolve().is_err());
+}
+
+#[test]
+fn test_builder_root() {
+    let dir = tempdir().unwrap();
+    let mut root = TempRoot::new();
+    root.candidate(dir.path().join("missing"))
+        .candidate(dir.path());
+
+    let mut builder = Builder::new();
+    builder.root(&root);
+    let file = builder.tempfile().unwrap();
+    assert_eq!(file.path().parent().unwrap(), dir.path());
+    let tmp_dir = builder.tempdir().unwrap();
+    assert_eq!(tmp_dir.path().parent().unwrap(), dir.path());
+    builder.tempfile_unnamed().unwrap();
+
+    let err = Builder::new()
+        .root(&TempRoot::new())
+        .tempfile()
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+}
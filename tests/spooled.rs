@@ -1,8 +1,8 @@
 #![deny(rust_2018_idioms)]
 
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::io::{self, IoSlice, IoSliceMut, Read, Seek, SeekFrom, Write};
 
-use tempfile::{spooled_tempfile, SpooledTempFile};
+use tempfile::{spooled_tempfile, SpoolBudget, SpooledTempFile};
 
 #[test]
 fn test_automatic_rollover() {
@@ -305,3 +305,351 @@ fn test_set_len_rollover() {
     assert_eq!(t.read_to_end(&mut buf).unwrap(), 20);
     assert_eq!(buf.as_slice(), b"abcde\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0");
 }
+
+#[test]
+fn test_on_roll() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let spilled = Arc::new(AtomicUsize::new(0));
+    let mut t = spooled_tempfile(10);
+    {
+        let spilled = spilled.clone();
+        t.on_roll(move |size, file| {
+            assert_eq!(file.metadata()?.len(), size as u64);
+            spilled.store(size, Ordering::SeqCst);
+            Ok(())
+        });
+    }
+
+    assert_eq!(t.write(b"abcde").unwrap(), 5);
+    assert_eq!(spilled.load(Ordering::SeqCst), 0);
+    assert_eq!(t.write(b"fghijk").unwrap(), 6);
+    assert!(t.is_rolled());
+    assert_eq!(spilled.load(Ordering::SeqCst), 5);
+
+    // already rolled, the hook isn't called again
+    assert!(t.roll().is_ok());
+    assert_eq!(spilled.load(Ordering::SeqCst), 5);
+}
+
+#[test]
+fn test_on_roll_veto() {
+    let mut t = spooled_tempfile(10);
+    t.on_roll(|_, _| {
+        Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "no spilling",
+        ))
+    });
+
+    assert_eq!(t.write(b"abcde").unwrap(), 5);
+    let err = t.write(b"fghijk").unwrap_err();
+    assert_eq!(err.to_string(), "no spilling");
+    assert!(!t.is_rolled());
+    assert!(t.roll().is_err());
+    assert!(t.set_len(20).is_err());
+    assert!(!t.is_rolled());
+
+    let mut buf = Vec::new();
+    assert_eq!(t.seek(SeekFrom::Start(0)).unwrap(), 0);
+    assert_eq!(t.read_to_end(&mut buf).unwrap(), 5);
+    assert_eq!(buf.as_slice(), b"abcde");
+}
+
+#[test]
+fn test_budget() {
+    use std::sync::Arc;
+
+    let budget = Arc::new(SpoolBudget::new(20));
+    let mut a = SpooledTempFile::with_budget(100, budget.clone());
+    let mut b = SpooledTempFile::with_budget(100, budget.clone());
+    assert_eq!(budget.limit(), 20);
+    assert_eq!(budget.used(), 0);
+
+    assert_eq!(a.write(b"abcdefghij").unwrap(), 10);
+    assert_eq!(budget.used(), 10);
+    assert_eq!(b.write(b"abcde").unwrap(), 5);
+    assert_eq!(budget.used(), 15);
+
+    // overwriting existing data doesn't need more memory
+    assert_eq!(a.seek(SeekFrom::Start(0)).unwrap(), 0);
+    assert_eq!(a.write(b"ABCDE").unwrap(), 5);
+    assert!(!a.is_rolled());
+    assert_eq!(budget.used(), 15);
+
+    // the budget is exhausted, so the requesting spool rolls over
+    assert_eq!(b.write(b"fghijk").unwrap(), 6);
+    assert!(b.is_rolled());
+    assert!(!a.is_rolled());
+    assert_eq!(budget.used(), 10);
+
+    assert!(a.set_len(2).is_ok());
+    assert_eq!(budget.used(), 2);
+    assert!(a.set_len(30).is_ok());
+    assert!(a.is_rolled());
+    assert_eq!(budget.used(), 0);
+
+    let mut buf = Vec::new();
+    assert_eq!(b.seek(SeekFrom::Start(0)).unwrap(), 0);
+    assert_eq!(b.read_to_end(&mut buf).unwrap(), 11);
+    assert_eq!(buf.as_slice(), b"abcdefghijk");
+}
+
+#[test]
+fn test_budget_released_on_drop() {
+    use std::sync::Arc;
+
+    let budget = Arc::new(SpoolBudget::new(20));
+    let mut t = SpooledTempFile::with_budget(100, budget.clone());
+    assert_eq!(t.write(b"abcdefghij").unwrap(), 10);
+    assert_eq!(budget.used(), 10);
+    drop(t);
+    assert_eq!(budget.used(), 0);
+}
+
+fn test_vectored(t: &mut SpooledTempFile) {
+    // Not every platform supports vectored I/O on files, and the fallback only
+    // uses the first buffer, so don't assume all buffers are used.
+    let bufs = [IoSlice::new(b"abcde"), IoSlice::new(b"fghij")];
+    let written = t.write_vectored(&bufs).unwrap();
+    assert!(written >= 5);
+    t.write_all(&b"abcdefghij"[written..]).unwrap();
+    assert_eq!(t.seek(SeekFrom::Start(0)).unwrap(), 0);
+
+    let mut first = [0; 4];
+    let mut second = [0; 6];
+    let read = t
+        .read_vectored(&mut [IoSliceMut::new(&mut first), IoSliceMut::new(&mut second)])
+        .unwrap();
+    assert!(read >= 4);
+    t.read_exact(&mut second[read - 4..]).unwrap();
+    assert_eq!(&first, b"abcd");
+    assert_eq!(&second, b"efghij");
+}
+
+#[test]
+fn test_vectored_buffer() {
+    let mut t = spooled_tempfile(100);
+    test_vectored(&mut t);
+    assert!(!t.is_rolled());
+}
+
+#[test]
+fn test_vectored_file() {
+    let mut t = spooled_tempfile(100);
+    t.roll().unwrap();
+    test_vectored(&mut t);
+}
+
+#[test]
+fn test_vectored_rollover() {
+    let mut t = spooled_tempfile(8);
+    let bufs = [IoSlice::new(b"abcde"), IoSlice::new(b"fghij")];
+    assert!(t.write_vectored(&bufs).unwrap() > 0);
+    assert!(t.is_rolled());
+}
+
+fn test_positional(t: &mut SpooledTempFile) {
+    assert_eq!(t.write(b"abcdefghij").unwrap(), 10);
+    assert_eq!(t.seek(SeekFrom::Start(3)).unwrap(), 3);
+
+    let mut buf = [0; 4];
+    assert_eq!(t.read_at(&mut buf, 5).unwrap(), 4);
+    assert_eq!(&buf, b"fghi");
+    assert_eq!(t.read_at(&mut buf, 8).unwrap(), 2);
+    assert_eq!(&buf[..2], b"ij");
+    assert_eq!(t.read_at(&mut buf, 20).unwrap(), 0);
+
+    assert_eq!(t.write_at(b"XY", 1).unwrap(), 2);
+    assert_eq!(t.write_at(b"Z", 12).unwrap(), 1);
+
+    // the position hasn't moved
+    #[allow(clippy::seek_from_current)]
+    let pos = t.seek(SeekFrom::Current(0)).unwrap();
+    assert_eq!(pos, 3);
+
+    let mut buf = Vec::new();
+    assert_eq!(t.seek(SeekFrom::Start(0)).unwrap(), 0);
+    assert_eq!(t.read_to_end(&mut buf).unwrap(), 13);
+    assert_eq!(buf.as_slice(), b"aXYdefghij\0\0Z");
+}
+
+#[test]
+fn test_write_at_overflow() {
+    // The offset can't be addressed in memory, so the data is spilled to
+    // disk, where writing that far fails.
+    let mut t = spooled_tempfile(usize::MAX);
+    assert!(t.write_at(b"x", u64::MAX).is_err());
+    assert!(t.is_rolled());
+}
+
+#[test]
+fn test_positional_buffer() {
+    let mut t = spooled_tempfile(100);
+    test_positional(&mut t);
+    assert!(!t.is_rolled());
+}
+
+#[test]
+fn test_positional_file() {
+    let mut t = spooled_tempfile(100);
+    t.roll().unwrap();
+    test_positional(&mut t);
+}
+
+#[test]
+fn test_positional_rollover() {
+    let mut t = spooled_tempfile(10);
+    assert_eq!(t.write(b"abcde").unwrap(), 5);
+    assert_eq!(t.write_at(b"klmno", 10).unwrap(), 5);
+    assert!(t.is_rolled());
+    #[allow(clippy::seek_from_current)]
+    let pos = t.seek(SeekFrom::Current(0)).unwrap();
+    assert_eq!(pos, 5);
+
+    let mut buf = Vec::new();
+    assert_eq!(t.seek(SeekFrom::Start(0)).unwrap(), 0);
+    assert_eq!(t.read_to_end(&mut buf).unwrap(), 15);
+    assert_eq!(buf.as_slice(), b"abcde\0\0\0\0\0klmno");
+}
+
+#[test]
+fn test_into_bytes() {
+    let mut t = spooled_tempfile(10);
+    assert_eq!(t.write(b"abcde").unwrap(), 5);
+    assert_eq!(t.into_bytes().unwrap(), b"abcde");
+
+    let mut t = spooled_tempfile(10);
+    assert_eq!(t.write(b"abcdefghijklmno").unwrap(), 15);
+    assert!(t.is_rolled());
+    assert_eq!(t.into_bytes().unwrap(), b"abcdefghijklmno");
+}
+
+#[test]
+fn test_into_reader() {
+    for &max_size in &[100, 1] {
+        let mut t = spooled_tempfile(max_size);
+        assert_eq!(t.write(b"abcde").unwrap(), 5);
+        let mut reader = t.into_reader().unwrap();
+        let mut buf = Vec::new();
+        assert_eq!(reader.read_to_end(&mut buf).unwrap(), 5);
+        assert_eq!(buf.as_slice(), b"abcde");
+    }
+}
+
+#[test]
+fn test_into_file() {
+    let mut t = spooled_tempfile(10);
+    assert_eq!(t.write(b"abcde").unwrap(), 5);
+    assert!(!t.is_rolled());
+    let mut file = t.into_file().unwrap();
+    #[allow(clippy::seek_from_current)]
+    let pos = file.seek(SeekFrom::Current(0)).unwrap();
+    assert_eq!(pos, 5);
+    let mut buf = Vec::new();
+    assert_eq!(file.seek(SeekFrom::Start(0)).unwrap(), 0);
+    assert_eq!(file.read_to_end(&mut buf).unwrap(), 5);
+    assert_eq!(buf.as_slice(), b"abcde");
+}
+
+#[test]
+fn test_persist_in_memory() {
+    let dir = tempfile::tempdir().unwrap();
+    let dest = dir.path().join("persisted");
+
+    let mut t = spooled_tempfile(100);
+    assert_eq!(t.write(b"abcde").unwrap(), 5);
+    assert!(t.path().is_none());
+    let mut file = t.persist(&dest).unwrap();
+    #[allow(clippy::seek_from_current)]
+    let pos = file.seek(SeekFrom::Current(0)).unwrap();
+    assert_eq!(pos, 5);
+    assert_eq!(std::fs::read(&dest).unwrap(), b"abcde");
+
+    // only the persisted file is left behind
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[test]
+fn test_persist_unnamed_rollover() {
+    let dir = tempfile::tempdir().unwrap();
+    let dest = dir.path().join("persisted");
+
+    let mut t = spooled_tempfile(1);
+    assert_eq!(t.write(b"abcde").unwrap(), 5);
+    assert!(t.is_rolled());
+    assert!(t.path().is_none());
+    t.persist(&dest).unwrap();
+    assert_eq!(std::fs::read(&dest).unwrap(), b"abcde");
+}
+
+#[test]
+fn test_persist_named_rollover() {
+    let dir = tempfile::tempdir().unwrap();
+    let dest = dir.path().join("persisted");
+
+    let mut t = spooled_tempfile(1);
+    t.spill_named_in(dir.path());
+    assert_eq!(t.write(b"abcde").unwrap(), 5);
+    assert!(t.is_rolled());
+    let spill_path = t.path().unwrap().to_owned();
+    assert!(spill_path.starts_with(dir.path()));
+    assert_eq!(std::fs::read(&spill_path).unwrap(), b"abcde");
+
+    t.persist(&dest).unwrap();
+    assert!(!spill_path.exists());
+    assert_eq!(std::fs::read(&dest).unwrap(), b"abcde");
+}
+
+#[test]
+fn test_named_rollover_removed_on_drop() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let mut t = spooled_tempfile(1);
+    t.spill_named_in(dir.path());
+    t.roll().unwrap();
+    let spill_path = t.path().unwrap().to_owned();
+    assert!(spill_path.exists());
+    drop(t);
+    assert!(!spill_path.exists());
+}
+
+#[test]
+fn test_persist_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let dest = dir.path().join("missing").join("persisted");
+
+    let mut t = spooled_tempfile(1);
+    t.spill_named_in(dir.path());
+    assert_eq!(t.write(b"abcde").unwrap(), 5);
+    let err = t.persist(&dest).unwrap_err();
+    assert_eq!(err.error.kind(), io::ErrorKind::NotFound);
+    assert_eq!(err.destination, dest);
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "failed to persist spooled temporary file to {:?}: {}",
+            dest, err.error
+        )
+    );
+
+    // the data is still there
+    let t = err.file;
+    assert!(t.path().unwrap().exists());
+    assert_eq!(t.into_bytes().unwrap(), b"abcde");
+}
+
+#[test]
+fn test_budget_released_on_into_bytes() {
+    use std::sync::Arc;
+
+    // The budget stops covering the data once it leaves the spool.
+    let budget = Arc::new(SpoolBudget::new(20));
+    let mut t = SpooledTempFile::with_budget(100, budget.clone());
+    assert_eq!(t.write(b"abcdefghij").unwrap(), 10);
+    assert_eq!(budget.used(), 10);
+    let data = t.into_bytes().unwrap();
+    assert_eq!(budget.used(), 0);
+    assert_eq!(data, b"abcdefghij");
+}
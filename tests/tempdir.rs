@@ -16,7 +16,7 @@ use std::path::Path;
 use std::sync::mpsc::channel;
 use std::thread;
 
-use tempfile::{Builder, TempDir};
+use tempfile::{Builder, CopyMode, TempDir};
 
 macro_rules! t {
     ($e:expr) => {
@@ -247,6 +247,164 @@ pub fn pass_as_asref_path() {
     }
 }
 
+#[test]
+fn test_close_in_background() {
+    let parent = t!(TempDir::new());
+    let tmp = t!(TempDir::new_in(parent.path()));
+    let path = tmp.path().to_path_buf();
+    t!(fs::create_dir(path.join("sub")));
+    t!(fs::write(path.join("sub").join("file"), b"abcde"));
+
+    tmp.close_in_background();
+    assert!(!path.exists());
+
+    tempfile::flush_background_cleanup();
+    assert_eq!(t!(fs::read_dir(parent.path())).count(), 0);
+}
+
+#[test]
+fn test_background_drop() {
+    let parent = t!(TempDir::new());
+    let tmp = t!(Builder::new()
+        .background_drop(true)
+        .tempdir_in(parent.path()));
+    let path = tmp.path().to_path_buf();
+    t!(fs::write(path.join("file"), b"abcde"));
+
+    drop(tmp);
+    assert!(!path.exists());
+
+    tempfile::flush_background_cleanup();
+    assert_eq!(t!(fs::read_dir(parent.path())).count(), 0);
+}
+
+fn make_template() -> TempDir {
+    let template = t!(TempDir::new());
+    t!(fs::create_dir(template.path().join("sub")));
+    t!(fs::write(template.path().join("top"), b"top"));
+    t!(fs::write(
+        template.path().join("sub").join("nested"),
+        b"nested"
+    ));
+    template
+}
+
+#[test]
+fn test_copy_from() {
+    let template = make_template();
+    let copy = t!(TempDir::copy_from(template.path()));
+    assert_ne!(copy.path(), template.path());
+    assert_eq!(t!(fs::read(copy.path().join("top"))), b"top");
+    assert_eq!(
+        t!(fs::read(copy.path().join("sub").join("nested"))),
+        b"nested"
+    );
+
+    // The copy is independent of the template.
+    t!(fs::write(copy.path().join("top"), b"changed"));
+    assert_eq!(t!(fs::read(template.path().join("top"))), b"top");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_copy_from_preserves_modes_and_symlinks() {
+    use std::os::unix::fs::{symlink, PermissionsExt};
+
+    let template = make_template();
+    let script = template.path().join("script.sh");
+    t!(fs::write(&script, b"#!/bin/sh\n"));
+    t!(fs::set_permissions(
+        &script,
+        fs::Permissions::from_mode(0o751)
+    ));
+    t!(fs::set_permissions(
+        template.path().join("sub"),
+        fs::Permissions::from_mode(0o555)
+    ));
+    t!(symlink("sub/nested", template.path().join("link")));
+
+    let copy = t!(Builder::new()
+        .prefix("fixtures")
+        .tempdir_from(template.path()));
+    let mode = |p: &Path| t!(fs::metadata(p)).permissions().mode() & 0o777;
+    assert_eq!(mode(&copy.path().join("script.sh")), 0o751);
+    // Directories stay writable by their owner, so that they can be removed.
+    assert_eq!(mode(&copy.path().join("sub")), 0o755);
+    assert_eq!(
+        t!(fs::read_link(copy.path().join("link"))),
+        Path::new("sub/nested")
+    );
+    assert_eq!(t!(fs::read(copy.path().join("link"))), b"nested");
+
+    let path = copy.path().to_path_buf();
+    t!(copy.close());
+    assert!(!path.exists());
+
+    // Allow the template to be cleaned up.
+    t!(fs::set_permissions(
+        template.path().join("sub"),
+        fs::Permissions::from_mode(0o755)
+    ));
+}
+
+#[test]
+fn test_copy_from_hardlink() {
+    let template = make_template();
+    let copy = t!(Builder::new()
+        .copy_mode(CopyMode::Hardlink)
+        .tempdir_from(template.path()));
+    assert_eq!(t!(fs::read(copy.path().join("top"))), b"top");
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        assert_eq!(t!(fs::metadata(template.path().join("top"))).nlink(), 2);
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_copy_from_hardlink_other_filesystem() {
+    // `/dev/shm` is a separate filesystem from the default temporary
+    // directory (unless `TMPDIR` points there), so the files can't be linked.
+    if !Path::new("/dev/shm").is_dir() {
+        return;
+    }
+    let template = t!(Builder::new().tempdir_in("/dev/shm"));
+    t!(fs::write(template.path().join("top"), b"top"));
+    let copy = t!(Builder::new()
+        .copy_mode(CopyMode::Hardlink)
+        .tempdir_from(template.path()));
+    assert_eq!(t!(fs::read(copy.path().join("top"))), b"top");
+}
+
+#[test]
+fn test_copy_from_reflink() {
+    let template = make_template();
+    match Builder::new()
+        .copy_mode(CopyMode::Reflink)
+        .tempdir_from(template.path())
+    {
+        Ok(copy) => {
+            assert_eq!(t!(fs::read(copy.path().join("top"))), b"top");
+            t!(fs::write(copy.path().join("top"), b"changed"));
+            assert_eq!(t!(fs::read(template.path().join("top"))), b"top");
+        }
+        // Reflinks aren't supported by the filesystem (or platform).
+        Err(e) => assert_eq!(
+            tempfile::Error::from_io_error(&e).unwrap().operation(),
+            tempfile::Operation::Copy
+        ),
+    }
+}
+
+#[test]
+fn test_copy_from_missing() {
+    let template = make_template();
+    let missing = template.path().join("missing");
+    assert!(Builder::new().tempdir_from(&missing).is_err());
+}
+
 #[test]
 fn main() {
     in_tmpdir(test_tempdir);
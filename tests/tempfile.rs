@@ -7,6 +7,7 @@ use std::{
     sync::mpsc::{sync_channel, TryRecvError},
     thread,
 };
+use tempfile::{Builder, TempFileStrategy};
 
 #[test]
 fn test_basic() {
@@ -66,3 +67,109 @@ fn test_pathological_cleaner() {
     drop(tx);
     cleaner_thread.join().expect("The cleaner thread failed");
 }
+
+#[test]
+fn test_with_info() {
+    let tmpdir = tempfile::tempdir().unwrap();
+    let (mut tmpfile, strategy) = tempfile::tempfile_in_with_info(&tmpdir).unwrap();
+    write!(tmpfile, "abcde").unwrap();
+    if cfg!(windows) {
+        assert_eq!(strategy, TempFileStrategy::DeleteOnClose);
+    } else if cfg!(not(target_os = "linux")) {
+        assert_eq!(strategy, TempFileStrategy::CreateUnlink);
+    }
+    drop(tmpfile);
+    assert_eq!(fs::read_dir(&tmpdir).unwrap().count(), 0);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_force_create_unlink() {
+    let tmpdir = tempfile::tempdir().unwrap();
+    let (_tmpfile, strategy) = Builder::new()
+        .strategy(TempFileStrategy::CreateUnlink)
+        .anonymous_in_with_info(&tmpdir)
+        .unwrap();
+    assert_eq!(strategy, TempFileStrategy::CreateUnlink);
+    assert_eq!(fs::read_dir(&tmpdir).unwrap().count(), 0);
+}
+
+#[test]
+fn test_force_o_tmpfile() {
+    let tmpdir = tempfile::tempdir().unwrap();
+    let result = Builder::new()
+        .strategy(TempFileStrategy::OTmpFile)
+        .anonymous_in_with_info(&tmpdir);
+    match result {
+        Ok((_, strategy)) => assert_eq!(strategy, TempFileStrategy::OTmpFile),
+        // Never falls back to a named file.
+        Err(_) => assert!(cfg!(not(target_os = "linux")) || is_tmpfile_unsupported(&tmpdir)),
+    }
+    assert_eq!(fs::read_dir(&tmpdir).unwrap().count(), 0);
+}
+
+fn is_tmpfile_unsupported(dir: &tempfile::TempDir) -> bool {
+    let (_, strategy) = tempfile::tempfile_in_with_info(dir).unwrap();
+    strategy != TempFileStrategy::OTmpFile
+}
+
+#[test]
+fn test_unnamed_with_builder() {
+    let tmpdir = tempfile::tempdir().unwrap();
+    let mut tmpfile = Builder::new()
+        .prefix("scratch")
+        .suffix(".bin")
+        .append(true)
+        .anonymous_in(&tmpdir)
+        .unwrap();
+    write!(tmpfile, "abc").unwrap();
+    tmpfile.seek(SeekFrom::Start(0)).unwrap();
+    write!(tmpfile, "de").unwrap();
+    tmpfile.seek(SeekFrom::Start(0)).unwrap();
+    let mut buf = String::new();
+    tmpfile.read_to_string(&mut buf).unwrap();
+    assert_eq!("abcde", buf);
+    assert_eq!(fs::read_dir(&tmpdir).unwrap().count(), 0);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_tmpfile_default_mode() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmpdir = tempfile::tempdir().unwrap();
+    let tmpfile = Builder::new()
+        .strategy(TempFileStrategy::OTmpFile)
+        .anonymous_in(&tmpdir);
+    // `O_TMPFILE` isn't supported everywhere.
+    let tmpfile = match tmpfile {
+        Ok(tmpfile) => tmpfile,
+        Err(_) => return,
+    };
+    // Like any other file created without an explicit mode.
+    let reference = fs::File::create(tmpdir.path().join("reference")).unwrap();
+    assert_eq!(
+        tmpfile.metadata().unwrap().permissions().mode(),
+        reference.metadata().unwrap().permissions().mode()
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unnamed_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmpdir = tempfile::tempdir().unwrap();
+    for strategy in &[TempFileStrategy::OTmpFile, TempFileStrategy::CreateUnlink] {
+        let tmpfile = Builder::new()
+            .permissions(fs::Permissions::from_mode(0o400))
+            .strategy(*strategy)
+            .anonymous_in(&tmpdir);
+        // `O_TMPFILE` isn't supported everywhere, but the fallback is.
+        if tmpfile.is_err() && *strategy == TempFileStrategy::OTmpFile {
+            continue;
+        }
+        let mode = tmpfile.unwrap().metadata().unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o400);
+    }
+}
@@ -0,0 +1,145 @@
+#![deny(rust_2018_idioms)]
+#![cfg(feature = "tokio")]
+
+use std::future::Future;
+use std::io::SeekFrom;
+
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+#[test]
+fn test_tempfile() {
+    block_on(async {
+        let mut file = tempfile::tokio::tempfile().await.unwrap();
+        file.write_all(b"abcde").await.unwrap();
+        file.seek(SeekFrom::Start(0)).await.unwrap();
+        let mut buf = String::new();
+        file.read_to_string(&mut buf).await.unwrap();
+        assert_eq!("abcde", buf);
+    });
+}
+
+#[test]
+fn test_named_tempfile() {
+    block_on(async {
+        let mut file = tempfile::tokio::NamedTempFile::new().await.unwrap();
+        let path = file.path().to_owned();
+        assert!(path.exists());
+
+        file.write_all(b"abcde").await.unwrap();
+        file.flush().await.unwrap();
+        assert_eq!(file.seek(SeekFrom::Start(1)).await.unwrap(), 1);
+        let mut buf = String::new();
+        file.read_to_string(&mut buf).await.unwrap();
+        assert_eq!("bcde", buf);
+
+        file.close().await.unwrap();
+        assert!(!path.exists());
+    });
+}
+
+#[test]
+fn test_named_tempfile_persist() {
+    block_on(async {
+        let dir = tempfile::tokio::TempDir::new().await.unwrap();
+        let dest = dir.path().join("persisted");
+
+        let mut file = tempfile::tokio::NamedTempFile::new_in(dir.path())
+            .await
+            .unwrap();
+        let path = file.path().to_owned();
+        file.write_all(b"abcde").await.unwrap();
+        file.flush().await.unwrap();
+
+        let mut file = file.persist(&dest).await.unwrap();
+        assert!(!path.exists());
+        file.seek(SeekFrom::Start(0)).await.unwrap();
+        let mut buf = String::new();
+        file.read_to_string(&mut buf).await.unwrap();
+        assert_eq!("abcde", buf);
+
+        let other = tempfile::tokio::NamedTempFile::new_in(dir.path())
+            .await
+            .unwrap();
+        let err = other.persist_noclobber(&dest).await.unwrap_err();
+        assert_eq!(err.error.kind(), std::io::ErrorKind::AlreadyExists);
+        assert!(err.file.path().exists());
+
+        dir.close().await.unwrap();
+    });
+}
+
+#[test]
+fn test_tempdir_drop() {
+    let path = block_on(async {
+        let dir = tempfile::tokio::TempDir::new().await.unwrap();
+        let path = dir.path().to_owned();
+        std::fs::write(path.join("file"), b"abcde").unwrap();
+        path
+    });
+    // the runtime waits for the removal to finish before shutting down
+    assert!(!path.exists());
+
+    let dir = block_on(tempfile::tokio::TempDir::new()).unwrap();
+    let path = dir.path().to_owned();
+    drop(dir);
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_tempdir_into_path() {
+    let path = block_on(async {
+        let dir = tempfile::tokio::TempDir::new().await.unwrap();
+        dir.into_path()
+    });
+    assert!(path.exists());
+    std::fs::remove_dir(path).unwrap();
+}
+
+#[test]
+fn test_spooled_tempfile() {
+    block_on(async {
+        let mut t = tempfile::tokio::spooled_tempfile(10);
+        t.write_all(b"abcde").await.unwrap();
+        assert!(!t.is_rolled());
+        assert_eq!(t.seek(SeekFrom::Start(1)).await.unwrap(), 1);
+        let mut buf = String::new();
+        t.read_to_string(&mut buf).await.unwrap();
+        assert_eq!("bcde", buf);
+
+        // this write doesn't fit in memory anymore
+        t.write_all(b"fghijklmno").await.unwrap();
+        assert!(t.is_rolled());
+        assert_eq!(t.seek(SeekFrom::Current(0)).await.unwrap(), 15);
+        assert_eq!(t.seek(SeekFrom::Start(0)).await.unwrap(), 0);
+        let mut buf = String::new();
+        t.read_to_string(&mut buf).await.unwrap();
+        assert_eq!("abcdefghijklmno", buf);
+    });
+}
+
+#[test]
+fn test_spooled_tempfile_roll_and_set_len() {
+    block_on(async {
+        let mut t = tempfile::tokio::SpooledTempFile::new(100);
+        t.write_all(b"abcdefghij").await.unwrap();
+        t.set_len(5).await.unwrap();
+        assert!(!t.is_rolled());
+
+        t.roll().await.unwrap();
+        assert!(t.is_rolled());
+        assert_eq!(t.seek(SeekFrom::Current(0)).await.unwrap(), 10);
+
+        t.set_len(8).await.unwrap();
+        assert_eq!(t.seek(SeekFrom::Start(0)).await.unwrap(), 0);
+        let mut buf = Vec::new();
+        t.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(buf.as_slice(), b"abcde\0\0\0");
+    });
+}